@@ -2,6 +2,7 @@ use miette::IntoDiagnostic;
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let mut a = vec![];
     let mut b = vec![];
 
@@ -17,6 +17,7 @@ pub fn evaluate_slope(start: i32, end: i32) -> Slope {
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let data: Vec<Vec<i32>> = input
         .lines()
         .map(|line| {
@@ -51,6 +51,7 @@ fn can_fix_by_removing_one(sequence: &[i32]) -> bool {
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let data: Vec<Vec<i32>> = input
         .lines()
         .map(|line| {
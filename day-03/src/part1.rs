@@ -100,6 +100,7 @@ pub fn parse_multiplication(input: &str) -> Result<Vec<String>> {
 /// Process input string and return sum of multiplications
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String> {
+    let input: &str = &grid::normalize(input);
     let expressions = parse_multiplication(input)?;
 
     let result: i32 = expressions
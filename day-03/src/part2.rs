@@ -119,6 +119,7 @@ fn parse_multiplication(input: &str) -> Result<Vec<String>> {
 /// Processes input string and returns sum of valid multiplication operations
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String> {
+    let input: &str = &grid::normalize(input);
     let result: i32 = parse_multiplication(input)?
         .iter()
         .map(|s| Multiplication::from_str(s))
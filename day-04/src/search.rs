@@ -0,0 +1,360 @@
+use rayon::prelude::*;
+
+/// Represents a 2D matrix of bytes
+pub type Matrix = Vec<Vec<u8>>;
+type Coordinate = (usize, usize);
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Position {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Position {
+    fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    fn to_coordinate(self) -> Coordinate {
+        (self.row, self.col)
+    }
+}
+
+/// Represents possible directions for word search
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Direction {
+    WestToEast,   // →
+    EastToWest,   // ←
+    NorthToSouth, // ↓
+    SouthToNorth, // ↑
+    SWtoNE,       // ↗
+    NEtoSW,       // ↙
+    NWtoSE,       // ↘
+    SEtoNW,       // ↖
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::WestToEast,
+        Direction::EastToWest,
+        Direction::NorthToSouth,
+        Direction::SouthToNorth,
+        Direction::SWtoNE,
+        Direction::NEtoSW,
+        Direction::NWtoSE,
+        Direction::SEtoNW,
+    ];
+
+    pub const DIAGONALS: [Direction; 4] = [
+        Direction::SWtoNE,
+        Direction::NEtoSW,
+        Direction::NWtoSE,
+        Direction::SEtoNW,
+    ];
+
+    /// Row and column change for one step in this direction
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::WestToEast => (0, 1),
+            Direction::EastToWest => (0, -1),
+            Direction::NorthToSouth => (1, 0),
+            Direction::SouthToNorth => (-1, 0),
+            Direction::SWtoNE => (-1, 1),
+            Direction::NEtoSW => (1, -1),
+            Direction::NWtoSE => (1, 1),
+            Direction::SEtoNW => (-1, -1),
+        }
+    }
+
+    /// Maps a position in the matrix produced by [`MatrixOps::transform_matrix`] back
+    /// to the original `rows` by `cols` grid, or `None` if it falls on diagonal padding
+    fn transform_coords(&self, pos: Position, rows: usize, cols: usize) -> Option<Coordinate> {
+        let (mut row, mut col) = pos.to_coordinate();
+
+        match self {
+            Direction::WestToEast => {}
+            Direction::EastToWest => {
+                (row, col) = MatrixOps::unreverse_coords(row, col, cols);
+            }
+            Direction::NorthToSouth => {
+                (row, col) = MatrixOps::untranspose_coords(row, col);
+            }
+            Direction::SouthToNorth => {
+                (row, col) = MatrixOps::unreverse_coords(row, col, rows);
+                (row, col) = MatrixOps::untranspose_coords(row, col);
+            }
+            Direction::NEtoSW => {
+                (row, col) = MatrixOps::untranspose_coords(row, col);
+                (row, col) = MatrixOps::unpad_diagonal_coords(row, col, rows, cols, false)?;
+            }
+            Direction::SWtoNE => {
+                (row, col) = MatrixOps::unreverse_coords(row, col, rows);
+                (row, col) = MatrixOps::untranspose_coords(row, col);
+                (row, col) = MatrixOps::unpad_diagonal_coords(row, col, rows, cols, false)?;
+            }
+            Direction::NWtoSE => {
+                (row, col) = MatrixOps::untranspose_coords(row, col);
+                (row, col) = MatrixOps::unpad_diagonal_coords(row, col, rows, cols, true)?;
+            }
+            Direction::SEtoNW => {
+                (row, col) = MatrixOps::unreverse_coords(row, col, rows);
+                (row, col) = MatrixOps::untranspose_coords(row, col);
+                (row, col) = MatrixOps::unpad_diagonal_coords(row, col, rows, cols, true)?;
+            }
+        }
+
+        Some((row, col))
+    }
+}
+
+/// A word found in the grid, starting at `position` and read in `direction`
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct Match {
+    pub position: Position,
+    pub direction: Direction,
+}
+
+impl Match {
+    /// Grid position of the letter at `index` within the matched word
+    pub fn letter(&self, index: usize) -> Position {
+        let (row_step, col_step) = self.direction.offset();
+        Position::new(
+            self.position
+                .row
+                .wrapping_add_signed(row_step * index as isize),
+            self.position
+                .col
+                .wrapping_add_signed(col_step * index as isize),
+        )
+    }
+}
+
+pub struct MatrixOps;
+
+impl MatrixOps {
+    /// Rearrange the matrix so reading each row left to right follows `direction`
+    pub fn transform_matrix(matrix: &[Vec<u8>], direction: Direction) -> Matrix {
+        match direction {
+            Direction::WestToEast => matrix.to_vec(),
+            Direction::EastToWest => Self::reverse_matrix(matrix),
+            Direction::NorthToSouth => Self::transpose_matrix(matrix),
+            Direction::SouthToNorth => Self::reverse_matrix(&Self::transpose_matrix(matrix)),
+            Direction::NEtoSW => Self::transpose_matrix(&Self::pad_diagonal(matrix, false)),
+            Direction::SWtoNE => {
+                Self::reverse_matrix(&Self::transpose_matrix(&Self::pad_diagonal(matrix, false)))
+            }
+            Direction::NWtoSE => Self::transpose_matrix(&Self::pad_diagonal(matrix, true)),
+            Direction::SEtoNW => {
+                Self::reverse_matrix(&Self::transpose_matrix(&Self::pad_diagonal(matrix, true)))
+            }
+        }
+    }
+
+    /// Shift each row sideways so diagonals line up as columns. Every row is padded
+    /// by `rows - 1` in total, so the result is `cols + rows - 1` wide.
+    pub fn pad_diagonal(matrix: &[Vec<u8>], reverse: bool) -> Matrix {
+        let rows = matrix.len();
+        matrix
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let (left, right) = if reverse {
+                    (rows - i - 1, i)
+                } else {
+                    (i, rows - i - 1)
+                };
+                [vec![b' '; left], row.to_vec(), vec![b' '; right]].concat()
+            })
+            .collect()
+    }
+
+    pub fn reverse_matrix(matrix: &[Vec<u8>]) -> Matrix {
+        matrix
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect()
+    }
+
+    pub fn transpose_matrix(matrix: &[Vec<u8>]) -> Matrix {
+        if matrix.is_empty() {
+            return vec![];
+        }
+
+        let cols = matrix[0].len();
+        (0..cols)
+            .map(|col| matrix.iter().map(|row| row[col]).collect())
+            .collect()
+    }
+
+    fn untranspose_coords(row: usize, col: usize) -> (usize, usize) {
+        (col, row)
+    }
+
+    fn unreverse_coords(row: usize, col: usize, width: usize) -> (usize, usize) {
+        (row, width - 1 - col)
+    }
+
+    fn unpad_diagonal_coords(
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+        reverse: bool,
+    ) -> Option<(usize, usize)> {
+        let padding = if reverse { rows - row - 1 } else { row };
+
+        let real_col = col.checked_sub(padding)?;
+        if real_col >= cols {
+            return None;
+        }
+
+        Some((row, real_col))
+    }
+}
+
+pub fn parse_matrix(input: &str) -> Matrix {
+    input.lines().map(|line| line.bytes().collect()).collect()
+}
+
+/// Find every occurrence of `word` in `grid` reading in any of `dirs`
+pub fn find_word(grid: &[Vec<u8>], word: &[u8], dirs: &[Direction]) -> Vec<Match> {
+    find_word_with_case(grid, word, dirs, false)
+}
+
+/// Same as [`find_word`], optionally treating upper and lower case letters as equal
+pub fn find_word_with_case(
+    grid: &[Vec<u8>],
+    word: &[u8],
+    dirs: &[Direction],
+    ignore_case: bool,
+) -> Vec<Match> {
+    if grid.is_empty() || word.is_empty() {
+        return vec![];
+    }
+    let (rows, cols) = (grid.len(), grid[0].len());
+
+    // Process all directions in parallel
+    dirs.par_iter()
+        .flat_map_iter(|&direction| {
+            MatrixOps::transform_matrix(grid, direction)
+                .into_iter()
+                .enumerate()
+                .flat_map(move |(row_idx, row)| find_in_row(&row, row_idx, word, ignore_case))
+                .filter_map(move |position| {
+                    let (row, col) = direction.transform_coords(position, rows, cols)?;
+                    Some(Match {
+                        position: Position::new(row, col),
+                        direction,
+                    })
+                })
+        })
+        .collect()
+}
+
+/// Start positions of `word` within a single transformed row
+fn find_in_row(row: &[u8], row_idx: usize, word: &[u8], ignore_case: bool) -> Vec<Position> {
+    row.windows(word.len())
+        .enumerate()
+        .filter(|(_, window)| {
+            if ignore_case {
+                window.eq_ignore_ascii_case(word)
+            } else {
+                *window == word
+            }
+        })
+        .map(|(i, _)| Position::new(row_idx, i))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const EXAMPLE: &str = "MMMSXXMASM\n\
+                           MSAMXMSMSA\n\
+                           AMXSXMAAMM\n\
+                           MSAMASMSMX\n\
+                           XMASAMXAMM\n\
+                           XXAMMXXAMA\n\
+                           SMSMSASXSS\n\
+                           SAXAMASAAA\n\
+                           MAMMMXMMMM\n\
+                           MXMXAXMASX";
+
+    #[test]
+    fn test_find_word_xmas() {
+        let grid = parse_matrix(EXAMPLE);
+        assert_eq!(18, find_word(&grid, b"XMAS", &Direction::ALL).len());
+    }
+
+    /// Walk every start cell and direction directly over the grid
+    fn brute_force(grid: &[Vec<u8>], word: &[u8]) -> HashSet<Match> {
+        let mut expected = HashSet::new();
+        for (row, line) in grid.iter().enumerate() {
+            for col in 0..line.len() {
+                for direction in Direction::ALL {
+                    let candidate = Match {
+                        position: Position::new(row, col),
+                        direction,
+                    };
+                    let spelled = word.iter().enumerate().all(|(i, letter)| {
+                        let Position { row, col } = candidate.letter(i);
+                        grid.get(row).and_then(|line| line.get(col)) == Some(letter)
+                    });
+                    if spelled {
+                        expected.insert(candidate);
+                    }
+                }
+            }
+        }
+        expected
+    }
+
+    #[test]
+    fn test_find_word_matches_letters() {
+        let grid = parse_matrix(EXAMPLE);
+        let found = find_word(&grid, b"MAS", &Direction::ALL)
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        assert_eq!(brute_force(&grid, b"MAS"), found);
+    }
+
+    #[test]
+    fn test_find_word_rectangular() {
+        // 4 rows by 6 columns, with MAS running down and to the right from (1, 3)
+        let grid = parse_matrix("XXXXXX\nXXXMXX\nXXXXAX\nXXXXXS");
+        let found = find_word(&grid, b"MAS", &Direction::ALL);
+        assert_eq!(
+            vec![Match {
+                position: Position::new(1, 3),
+                direction: Direction::NWtoSE,
+            }],
+            found
+        );
+
+        // And the transposed, 6 by 4 grid, checked against every direction
+        let tall = MatrixOps::transpose_matrix(&parse_matrix(EXAMPLE)[..4]);
+        let found = find_word(&tall, b"MAS", &Direction::ALL)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(brute_force(&tall, b"MAS"), found);
+
+        let wide = parse_matrix(EXAMPLE)[..4].to_vec();
+        let found = find_word(&wide, b"MAS", &Direction::ALL)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(brute_force(&wide, b"MAS"), found);
+    }
+
+    #[test]
+    fn test_find_in_row() {
+        let positions = find_in_row(b"MMASAS", 0, b"MAS", false);
+        assert_eq!(vec![Position::new(0, 1)], positions);
+
+        assert!(find_in_row(b"mMaSas", 0, b"MAS", false).is_empty());
+        let positions = find_in_row(b"mMaSas", 0, b"MAS", true);
+        assert_eq!(vec![Position::new(0, 1)], positions);
+    }
+}
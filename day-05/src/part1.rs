@@ -5,6 +5,7 @@ type Rules = HashMap<usize, Vec<usize>>;
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let data = input.lines().collect::<Vec<&str>>();
 
     let [rules, updates] = data.split(|line| line.is_empty()).collect::<Vec<_>>()[..] else {
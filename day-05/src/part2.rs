@@ -10,6 +10,7 @@ const RULE_SEPARATOR: char = '|';
 /// Returns the sum of middle elements from valid/fixed page sequences.
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let data = input.lines().collect::<Vec<&str>>();
 
     let [rules, updates] = data.split(|line| line.is_empty()).collect::<Vec<_>>()[..] else {
@@ -12,6 +12,16 @@ enum Direction {
 }
 
 impl Direction {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Self::North),
+            'v' => Some(Self::South),
+            '>' => Some(Self::East),
+            '<' => Some(Self::West),
+            _ => None,
+        }
+    }
+
     fn turn_right(&self) -> Self {
         match self {
             Self::North => Self::East,
@@ -48,38 +58,26 @@ impl Guard {
         path.insert(self.location);
     }
 
-    fn check_obstacle(&self, obstacles: &[Obstacle]) -> bool {
-        // Calculate next position based on current direction
-        let next = match self.direction {
-            Direction::North => Location {
-                x: self.location.x,
-                y: self.location.y.saturating_sub(1),
-            },
-            Direction::South => Location {
-                x: self.location.x,
-                y: self.location.y + 1,
-            },
-            Direction::East => Location {
-                x: self.location.x + 1,
-                y: self.location.y,
-            },
-            Direction::West => Location {
-                x: self.location.x.saturating_sub(1),
-                y: self.location.y,
-            },
-        };
+    /// The cell directly in front of the guard, or `None` past the top or left edge.
+    fn ahead(&self) -> Option<Location> {
+        let Location { x, y } = self.location;
+        match self.direction {
+            Direction::North => y.checked_sub(1).map(|y| Location { x, y }),
+            Direction::South => Some(Location { x, y: y + 1 }),
+            Direction::East => Some(Location { x: x + 1, y }),
+            Direction::West => x.checked_sub(1).map(|x| Location { x, y }),
+        }
+    }
 
+    fn check_obstacle(&self, obstacles: &[Obstacle]) -> bool {
         // Check if next position collides with any obstacle
-        obstacles.iter().any(|o| o.location == next)
+        self.ahead()
+            .is_some_and(|next| obstacles.iter().any(|o| o.location == next))
     }
 
     fn turn_right(&mut self) {
         self.direction = self.direction.turn_right();
     }
-
-    fn steps(&self) -> usize {
-        self.steps
-    }
 }
 
 struct Obstacle {
@@ -94,18 +92,6 @@ impl Obstacle {
     }
 }
 
-struct EmptyCell {
-    location: Location,
-}
-
-impl EmptyCell {
-    fn new(x: usize, y: usize) -> Self {
-        Self {
-            location: Location { x, y },
-        }
-    }
-}
-
 type Grid = Vec<Vec<Location>>;
 
 struct Map {
@@ -119,7 +105,6 @@ impl Map {
     fn new(input: &str) -> Self {
         let mut guard = Guard::default();
         let mut obstacles = vec![];
-        let mut empty_cells = vec![];
 
         // Get dimensions from input
         let rows = input.lines().count();
@@ -132,21 +117,19 @@ impl Map {
         for (y, line) in input.lines().enumerate() {
             for (x, c) in line.chars().enumerate() {
                 match c {
-                    START_POS => {
-                        guard = Guard {
-                            location: Location { x, y },
-                            direction: Direction::North,
-                            steps: 0, // Start at 0
-                        };
-                        path.insert(Location { x, y });
-                    }
                     OBSTACLE => {
                         obstacles.push(Obstacle::new(x, y));
                     }
-                    EMPTY_SPACE => {
-                        empty_cells.push(EmptyCell::new(x, y));
+                    _ => {
+                        if let Some(direction) = Direction::from_char(c) {
+                            guard = Guard {
+                                location: Location { x, y },
+                                direction,
+                                steps: 0, // Start at 0
+                            };
+                            path.insert(Location { x, y });
+                        }
                     }
-                    _ => {}
                 }
 
                 grid[y][x] = Location { x, y }; // Fix grid access
@@ -161,30 +144,25 @@ impl Map {
         }
     }
 
-    fn _steps(&self) -> usize {
-        self.guard.steps()
-    }
-
     fn unique_locations(&self) -> usize {
         self.path.len()
     }
 
-    fn guard_location(&self) -> &Location {
-        &self.guard.location
+    // Add bounds checking as a Map method
+    fn is_within_bounds(&self, location: &Location) -> bool {
+        location.x < self.grid[0].len() && location.y < self.grid.len()
     }
 
-    // Add bounds checking as a Map method
-    fn is_within_bounds(&self) -> bool {
-        let location = self.guard_location();
-        location.x > 0
-            && location.y > 0
-            && location.x < self.grid[0].len() - 1
-            && location.y < self.grid.len() - 1
+    /// Whether the guard's next move keeps it on the map.
+    fn can_advance(&self) -> bool {
+        self.guard
+            .ahead()
+            .is_some_and(|next| self.is_within_bounds(&next))
     }
 
     // Add method to track path
     fn track_path(&mut self) -> Result<(), miette::Error> {
-        while self.is_within_bounds() {
+        while self.can_advance() {
             self.walk();
         }
         Ok(())
@@ -201,11 +179,12 @@ impl Map {
 }
 
 const OBSTACLE: char = '#';
-const START_POS: char = '^';
-const EMPTY_SPACE: char = '.';
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
+    // Same checks as part2::distinct_positions, so both give the same answer or error
+    crate::part2::validate_map(input)?;
     let mut map = Map::new(input);
     map.track_path()?;
 
@@ -231,4 +210,46 @@ mod tests {
         assert_eq!("41", process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_process_border() -> miette::Result<()> {
+        // The guard starts on the bottom row and leaves through row 0
+        let input = "......
+......
+...#..
+.#...#
+....#.
+#..^..";
+        assert_eq!("8", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_guard_directions() -> miette::Result<()> {
+        // Facing east, the guard walks straight off the map
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..>.....
+........#.
+#.........
+......#...";
+        assert_eq!("6", process(input)?);
+
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..<.....
+........#.
+#.........
+......#...";
+        assert_eq!("26", process(input)?);
+        Ok(())
+    }
 }
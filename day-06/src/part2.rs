@@ -1,9 +1,10 @@
 use std::collections::HashSet;
 
 use miette::*;
+use rayon::prelude::*;
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
-enum Direction {
+pub enum Direction {
     #[default]
     North, // ^
     South, // v
@@ -12,6 +13,16 @@ enum Direction {
 }
 
 impl Direction {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Self::North),
+            'v' => Some(Self::South),
+            '>' => Some(Self::East),
+            '<' => Some(Self::West),
+            _ => None,
+        }
+    }
+
     fn turn_right(&self) -> Self {
         match self {
             Self::North => Self::East,
@@ -51,29 +62,21 @@ impl Guard {
         })
     }
 
-    fn check_obstacle(&self, obstacles: &[Obstacle]) -> bool {
-        // Calculate next position based on current direction
-        let next = match self.direction {
-            Direction::North => Location {
-                x: self.location.x,
-                y: self.location.y.saturating_sub(1),
-            },
-            Direction::South => Location {
-                x: self.location.x,
-                y: self.location.y + 1,
-            },
-            Direction::East => Location {
-                x: self.location.x + 1,
-                y: self.location.y,
-            },
-            Direction::West => Location {
-                x: self.location.x.saturating_sub(1),
-                y: self.location.y,
-            },
-        };
+    /// The cell directly in front of the guard, or `None` past the top or left edge.
+    fn ahead(&self) -> Option<Location> {
+        let Location { x, y } = self.location;
+        match self.direction {
+            Direction::North => y.checked_sub(1).map(|y| Location { x, y }),
+            Direction::South => Some(Location { x, y: y + 1 }),
+            Direction::East => Some(Location { x: x + 1, y }),
+            Direction::West => x.checked_sub(1).map(|x| Location { x, y }),
+        }
+    }
 
+    fn check_obstacle(&self, obstacles: &[Obstacle]) -> bool {
         // Check if next position collides with any obstacle
-        obstacles.iter().any(|o| o.location == next)
+        self.ahead()
+            .is_some_and(|next| obstacles.iter().any(|o| o.location == next))
     }
 
     fn turn_right(&mut self) {
@@ -102,131 +105,478 @@ struct PathEntry {
     direction: Direction,
 }
 
+/// How a guard's walk ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// The guard stepped off the map.
+    Exited,
+    /// The guard came back to a location and direction it had already been in.
+    Looped(Location),
+    /// The guard walked the allowed number of steps without doing either.
+    StepLimit,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 struct Map {
-    guard: Guard,
+    // Every guard in reading order. The single-guard methods follow the first one.
+    guards: Vec<Guard>,
     obstacles: Vec<Obstacle>,
     grid: Grid,
     path: HashSet<PathEntry>,
+    // Sorted obstacle x coordinates for each row, and y coordinates for each column
+    row_obstacles: Vec<Vec<usize>>,
+    column_obstacles: Vec<Vec<usize>>,
 }
 
 impl Map {
-    fn new(input: &str) -> Self {
-        let mut guard = Guard::default();
+    /// Errors unless the map passes [`validate_map`], so there is always a guard.
+    fn new(input: &str) -> miette::Result<Self> {
+        validate_map(input)?;
+        let mut guards = vec![];
         let mut obstacles = vec![];
 
         let rows = input.lines().count();
         let cols = input.lines().next().map_or(0, |line| line.len());
         let mut path: HashSet<PathEntry> = HashSet::with_capacity(rows * cols);
         let mut grid = vec![vec![Location::default(); cols]; rows];
+        let mut row_obstacles = vec![vec![]; rows];
+        let mut column_obstacles = vec![vec![]; cols];
 
         for (y, line) in input.lines().enumerate() {
             for (x, c) in line.chars().enumerate() {
                 match c {
-                    START_POS => {
-                        guard = Guard {
-                            location: Location { x, y },
-                            direction: Direction::North,
-                            steps: 0, // Start at 0
-                        };
-                        path.insert(PathEntry {
-                            location: Location { x, y },
-                            direction: guard.direction.clone(),
-                        });
-                    }
                     OBSTACLE => {
                         obstacles.push(Obstacle::new(x, y));
+                        // Scanning row by row keeps both indexes sorted
+                        row_obstacles[y].push(x);
+                        column_obstacles[x].push(y);
+                    }
+                    _ => {
+                        if let Some(direction) = Direction::from_char(c) {
+                            guards.push(Guard {
+                                location: Location { x, y },
+                                direction,
+                                steps: 0, // Start at 0
+                            });
+                        }
                     }
-                    _ => {}
                 }
 
                 grid[y][x] = Location { x, y }; // Fix grid access
             }
         }
 
-        Self {
-            guard,
+        path.insert(PathEntry {
+            location: guards[0].location,
+            direction: guards[0].direction.clone(),
+        });
+
+        Ok(Self {
+            guards,
             obstacles,
             grid,
             path,
+            row_obstacles,
+            column_obstacles,
+        })
+    }
+
+    fn guard_location(&self) -> &Location {
+        &self.guards[0].location
+    }
+
+    fn is_within_bounds(&self, location: &Location) -> bool {
+        location.x < self.grid[0].len() && location.y < self.grid.len()
+    }
+
+    /// Whether the guard's next move keeps it on the map.
+    fn can_advance(&self) -> bool {
+        self.guards[0]
+            .ahead()
+            .is_some_and(|next| self.is_within_bounds(&next))
+    }
+
+    /// The cell the guard ends up in when walking from `location` until the next
+    /// obstacle, or `None` if nothing stops it before it leaves the map.
+    fn next_stop(&self, location: Location, direction: &Direction) -> Option<Location> {
+        let Location { x, y } = location;
+        match direction {
+            Direction::North => {
+                let column = &self.column_obstacles[x];
+                let before = column.partition_point(|&oy| oy < y);
+                before.checked_sub(1).map(|i| Location {
+                    x,
+                    y: column[i] + 1,
+                })
+            }
+            Direction::South => {
+                let column = &self.column_obstacles[x];
+                let after = column.partition_point(|&oy| oy <= y);
+                column.get(after).map(|&oy| Location { x, y: oy - 1 })
+            }
+            Direction::East => {
+                let row = &self.row_obstacles[y];
+                let after = row.partition_point(|&ox| ox <= x);
+                row.get(after).map(|&ox| Location { x: ox - 1, y })
+            }
+            Direction::West => {
+                let row = &self.row_obstacles[y];
+                let before = row.partition_point(|&ox| ox < x);
+                before.checked_sub(1).map(|i| Location { x: row[i] + 1, y })
+            }
         }
     }
 
-    fn unique_locations(&self) -> usize {
-        self.path.len()
+    /// The last cell on the map when walking from `location` without obstruction.
+    fn edge(&self, location: Location, direction: &Direction) -> Location {
+        match direction {
+            Direction::North => Location { y: 0, ..location },
+            Direction::South => Location {
+                y: self.grid.len() - 1,
+                ..location
+            },
+            Direction::East => Location {
+                x: self.grid[0].len() - 1,
+                ..location
+            },
+            Direction::West => Location { x: 0, ..location },
+        }
     }
 
-    fn guard_location(&self) -> &Location {
-        &self.guard.location
+    /// Jumps the guard from obstacle to obstacle until it leaves the map or gets
+    /// caught in a loop, recording every cell passed on the way.
+    fn trace_path(&mut self) -> Outcome {
+        self.trace_path_with_limit(None)
     }
 
-    fn is_within_bounds(&self) -> bool {
-        let location = self.guard_location();
-        location.x > 0
-            && location.y > 0
-            && location.x < self.grid[0].len() - 1
-            && location.y < self.grid.len() - 1
+    /// Like [`trace_path`](Self::trace_path), but gives up with
+    /// [`Outcome::StepLimit`] once the guard has walked `max_steps` cells.
+    fn trace_path_with_limit(&mut self, max_steps: Option<usize>) -> Outcome {
+        let mut steps = 0;
+        loop {
+            let direction = self.guards[0].direction.clone();
+            let stop = self.next_stop(self.guards[0].location, &direction);
+            let end = stop.unwrap_or_else(|| self.edge(self.guards[0].location, &direction));
+
+            while self.guards[0].location != end {
+                if max_steps.is_some_and(|max_steps| steps >= max_steps) {
+                    return Outcome::StepLimit;
+                }
+                self.guards[0].walk(&mut self.path);
+                steps += 1;
+            }
+            if stop.is_none() {
+                return Outcome::Exited;
+            }
+
+            self.guards[0].turn_right();
+            if !self.path.insert(PathEntry {
+                location: end,
+                direction: self.guards[0].direction.clone(),
+            }) {
+                return Outcome::Looped(end);
+            }
+        }
+    }
+
+    /// Whether an extra obstacle at `obstacle` traps the guard in a loop. Only the
+    /// turns are tracked, since any loop has to repeat one of them.
+    fn creates_loop(&self, obstacle: Location) -> bool {
+        matches!(self.outcome_with(obstacle, None), Outcome::Looped(_))
+    }
+
+    /// How the guard's walk ends with an extra obstacle at `obstacle`, giving up
+    /// with [`Outcome::StepLimit`] past `max_steps` cells.
+    fn outcome_with(&self, obstacle: Location, max_steps: Option<usize>) -> Outcome {
+        let mut location = self.guards[0].location;
+        let mut direction = self.guards[0].direction.clone();
+        let mut turns = HashSet::new();
+        let mut steps = 0;
+
+        loop {
+            let stop = match (
+                self.next_stop(location, &direction),
+                stop_before(location, &direction, obstacle),
+            ) {
+                (Some(a), Some(b)) => Some(if distance(location, a) < distance(location, b) {
+                    a
+                } else {
+                    b
+                }),
+                (a, b) => a.or(b),
+            };
+            let Some(stop) = stop else {
+                return Outcome::Exited;
+            };
+
+            steps += distance(location, stop);
+            if max_steps.is_some_and(|max_steps| steps > max_steps) {
+                return Outcome::StepLimit;
+            }
+
+            location = stop;
+            direction = direction.turn_right();
+            if !turns.insert(PathEntry {
+                location,
+                direction: direction.clone(),
+            }) {
+                return Outcome::Looped(location);
+            }
+        }
     }
 
-    fn track_path(&mut self) -> Result<Option<Location>, miette::Error> {
-        while self.is_within_bounds() {
+    /// Walks each guard in turn, one cell at a time, until it exits or loops. Guards
+    /// don't get in each other's way, and afterwards `path` holds everywhere any of
+    /// them went.
+    fn track_paths(&mut self) -> Result<Vec<Outcome>, miette::Error> {
+        let mut outcomes = Vec::with_capacity(self.guards.len());
+        let mut path = HashSet::new();
+
+        for guard in std::mem::take(&mut self.guards) {
+            let mut patrol = Map {
+                path: HashSet::from([PathEntry {
+                    location: guard.location,
+                    direction: guard.direction.clone(),
+                }]),
+                guards: vec![guard],
+                ..self.clone()
+            };
+            outcomes.push(patrol.track_path()?);
+
+            path.extend(patrol.path);
+            self.guards.append(&mut patrol.guards);
+        }
+
+        self.path = path;
+        Ok(outcomes)
+    }
+
+    fn track_path(&mut self) -> Result<Outcome, miette::Error> {
+        while self.can_advance() {
             if self.walk() {
-                return Ok(Some(self.guard.location));
+                return Ok(Outcome::Looped(self.guards[0].location));
             }
         }
-        Ok(None)
+        Ok(Outcome::Exited)
     }
 
+    /// Advances the guard by one move, returning `true` once it repeats a
+    /// location and direction it has already been in.
     fn walk(&mut self) -> bool {
-        if self.guard.check_obstacle(&self.obstacles) {
-            self.guard.turn_right();
-            false
+        if self.guards[0].check_obstacle(&self.obstacles) {
+            self.guards[0].turn_right();
+            // Record turns as well, so a guard boxed in on all sides is caught spinning
+            !self.path.insert(PathEntry {
+                location: self.guards[0].location,
+                direction: self.guards[0].direction.clone(),
+            })
         } else {
-            self.guard.walk(&mut self.path)
+            self.guards[0].walk(&mut self.path)
         }
     }
 }
 
+/// The cell just short of `obstacle`, if it lies ahead of `location`.
+fn stop_before(location: Location, direction: &Direction, obstacle: Location) -> Option<Location> {
+    match direction {
+        Direction::North => {
+            (obstacle.x == location.x && obstacle.y < location.y).then(|| Location {
+                y: obstacle.y + 1,
+                ..obstacle
+            })
+        }
+        Direction::South => {
+            (obstacle.x == location.x && obstacle.y > location.y).then(|| Location {
+                y: obstacle.y - 1,
+                ..obstacle
+            })
+        }
+        Direction::East => {
+            (obstacle.y == location.y && obstacle.x > location.x).then(|| Location {
+                x: obstacle.x - 1,
+                ..obstacle
+            })
+        }
+        Direction::West => {
+            (obstacle.y == location.y && obstacle.x < location.x).then(|| Location {
+                x: obstacle.x + 1,
+                ..obstacle
+            })
+        }
+    }
+}
+
+fn distance(a: Location, b: Location) -> usize {
+    a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+}
+
 const OBSTACLE: char = '#';
-const START_POS: char = '^';
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<(Vec<Location>, String)> {
-    let mut original_map = Map::new(input);
-    original_map.track_path()?;
+    process_with_limit(input, None)
+}
 
-    let mut loop_locations = HashSet::new();
+/// Same as [`process`], but with `max_steps` set any walk, with or without the extra
+/// obstacle, that goes on for more cells than that is an error. Loops are caught
+/// either way, so this is only a safety valve for adversarial maps.
+#[tracing::instrument(skip(input))]
+pub fn process_with_limit(
+    input: &str,
+    max_steps: Option<usize>,
+) -> miette::Result<(Vec<Location>, String)> {
+    let input: &str = &grid::normalize(input);
+    let map = Map::new(input)?;
+    // Only ever reported with a limit set
+    let step_limit = || {
+        miette!(
+            "Guard walked more than {} steps",
+            max_steps.unwrap_or_default()
+        )
+    };
+
+    let mut traced = map.clone();
+    match traced.trace_path_with_limit(max_steps) {
+        Outcome::Exited => {}
+        Outcome::Looped(location) => {
+            return Err(miette!(
+                "Guard never leaves the map, it already loops at {:?}",
+                location
+            ))
+        }
+        Outcome::StepLimit => return Err(step_limit()),
+    }
+    let candidates = candidate_locations(&map, &traced);
+
+    // Each candidate only reads the shared map, so they can be checked in parallel
+    let loop_locations = candidates
+        .into_par_iter()
+        .map(|location| match map.outcome_with(location, max_steps) {
+            Outcome::Exited => Ok(None),
+            Outcome::Looped(_) => Ok(Some(location)),
+            Outcome::StepLimit => Err(step_limit()),
+        })
+        .collect::<miette::Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<HashSet<_>>();
 
-    // Skip first location (start position)
-    for step in original_map.path.iter().skip(1) {
-        let mut test_map = Map::new(input);
-        test_map.obstacles.push(Obstacle {
-            location: step.location,
-        });
+    Ok((
+        loop_locations.clone().into_iter().collect(),
+        loop_locations.len().to_string(),
+    ))
+}
 
-        let mut steps = 0;
-        const MAX_STEPS: usize = 1000; // Prevent infinite loops
+/// Same locations as [`process`], each paired with the direction the guard is
+/// moving in when it first reaches that cell, which is the only approach an obstacle
+/// placed there can ever block. Listed in the order the guard reaches them.
+pub fn process_with_directions(input: &str) -> miette::Result<Vec<(Location, Direction)>> {
+    let input: &str = &grid::normalize(input);
+    let map = Map::new(input)?;
+
+    let mut walked = map.clone();
+    let mut seen = HashSet::from([*map.guard_location()]);
+    let mut arrivals = vec![];
+    while walked.can_advance() {
+        if walked.walk() {
+            return Err(miette!(
+                "Guard never leaves the map, it already loops at {:?}",
+                walked.guard_location()
+            ));
+        }
+        if seen.insert(walked.guards[0].location) {
+            arrivals.push((
+                walked.guards[0].location,
+                walked.guards[0].direction.clone(),
+            ));
+        }
+    }
 
-        while test_map.is_within_bounds() {
-            steps += 1;
-            if steps > MAX_STEPS {
-                // Likely stuck in pattern without true loop
-                break;
-            }
+    Ok(arrivals
+        .into_par_iter()
+        .filter(|(location, _)| map.creates_loop(*location))
+        .collect())
+}
 
-            if test_map.guard.walk(&mut test_map.path) {
-                // Verify loop is real by checking path length
-                if test_map.path.len() > 2 {
-                    loop_locations.insert(step.location);
-                }
-                break;
-            }
+/// Number of distinct locations the guard visits before leaving the map, which is
+/// the part 1 answer. Turning on the spot does not count as a new location. If the
+/// guard never leaves, this counts every location on its loop. Errors on an empty or
+/// ragged map, or one without a guard.
+pub fn distinct_positions(input: &str) -> miette::Result<usize> {
+    let input: &str = &grid::normalize(input);
+    let mut map = Map::new(input)?;
+    map.trace_path();
+
+    Ok(map
+        .path
+        .iter()
+        .map(|entry| entry.location)
+        .collect::<HashSet<_>>()
+        .len())
+}
 
-            if test_map.guard.check_obstacle(&test_map.obstacles) {
-                test_map.guard.turn_right();
-            }
-        }
+/// Checks that `input` is a non-empty rectangle with at least one guard on it
+pub(crate) fn validate_map(input: &str) -> miette::Result<()> {
+    let width = input.lines().next().map_or(0, |line| line.chars().count());
+    if width == 0 {
+        return Err(miette!("Empty map"));
     }
+    if let Some((y, line)) = input
+        .lines()
+        .enumerate()
+        .find(|(_, line)| line.chars().count() != width)
+    {
+        return Err(miette!(
+            "Row {} is {} wide, expected {}",
+            y,
+            line.chars().count(),
+            width
+        ));
+    }
+    if !input.chars().any(|c| Direction::from_char(c).is_some()) {
+        return Err(miette!("No guard on the map"));
+    }
+    Ok(())
+}
+
+/// Number of distinct locations visited by any of the guards on the map, each
+/// patrolling until it leaves or starts going round in a loop. With a single guard
+/// this is the same as [`distinct_positions`].
+pub fn patrolled_positions(input: &str) -> miette::Result<usize> {
+    let input: &str = &grid::normalize(input);
+    let mut map = Map::new(input)?;
+    map.track_paths()?;
+
+    Ok(map
+        .path
+        .iter()
+        .map(|entry| entry.location)
+        .collect::<HashSet<_>>()
+        .len())
+}
+
+/// Same answer as [`process`], walking the guard one cell at a time. Much slower,
+/// but kept as a reference for the obstacle-jumping search.
+pub fn process_stepwise(input: &str) -> miette::Result<(Vec<Location>, String)> {
+    let input: &str = &grid::normalize(input);
+    let map = Map::new(input)?;
+
+    let mut traced = map.clone();
+    if let Outcome::Looped(location) = traced.track_path()? {
+        return Err(miette!(
+            "Guard never leaves the map, it already loops at {:?}",
+            location
+        ));
+    }
+    let candidates = candidate_locations(&map, &traced);
+
+    let loop_locations = candidates
+        .par_iter()
+        .map(|&location| Ok(creates_loop_stepwise(input, location)?.then_some(location)))
+        .collect::<miette::Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<HashSet<_>>();
 
     Ok((
         loop_locations.clone().into_iter().collect(),
@@ -234,6 +584,24 @@ pub fn process(input: &str) -> miette::Result<(Vec<Location>, String)> {
     ))
 }
 
+/// Every location the guard visits on the original map, apart from its start.
+fn candidate_locations(map: &Map, traced: &Map) -> HashSet<Location> {
+    traced
+        .path
+        .iter()
+        .map(|entry| entry.location)
+        .filter(|location| location != map.guard_location())
+        .collect()
+}
+
+fn creates_loop_stepwise(input: &str, location: Location) -> miette::Result<bool> {
+    // A fresh map per candidate, so the visited set starts empty
+    let mut test_map = Map::new(input)?;
+    test_map.obstacles.push(Obstacle { location });
+
+    Ok(matches!(test_map.track_path()?, Outcome::Looped(_)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -285,4 +653,296 @@ mod tests {
         assert_eq!(a_sorted, b_sorted);
         Ok(())
     }
+
+    #[test]
+    fn test_process_with_directions() -> miette::Result<()> {
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+        let mut placed = process_with_directions(input)?;
+        placed.sort_by_key(|(location, _)| *location);
+        assert_eq!(
+            vec![
+                (Location { x: 1, y: 8 }, Direction::West),
+                (Location { x: 3, y: 6 }, Direction::West),
+                (Location { x: 3, y: 8 }, Direction::West),
+                (Location { x: 6, y: 7 }, Direction::South),
+                (Location { x: 7, y: 7 }, Direction::East),
+                (Location { x: 7, y: 9 }, Direction::South),
+            ],
+            placed
+        );
+
+        let mut locations = process(input)?.0;
+        locations.sort();
+        assert_eq!(
+            locations,
+            placed
+                .into_iter()
+                .map(|(location, _)| location)
+                .collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_long_loop() -> miette::Result<()> {
+        // Blocking the guard's exit on the bottom row sends it round a loop of
+        // more than 1000 steps before any location and direction repeats
+        const DIM: usize = 300;
+        let mut grid = vec![vec!['.'; DIM]; DIM];
+        grid[1][2] = '#';
+        grid[2][DIM - 2] = '#';
+        grid[DIM - 2][DIM - 3] = '#';
+        grid[DIM - 3][2] = '^';
+        let input = grid
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let (locations, count) = process(&input)?;
+        assert!(locations.contains(&Location { x: 1, y: DIM - 3 }));
+        assert_eq!("1", count);
+
+        // The old cap of 1000 steps would have cut the guard's legitimate walk short
+        assert!(distinct_positions(&input)? > 1000);
+        assert!(process_with_limit(&input, Some(1000)).is_err());
+        assert_eq!("1", process_with_limit(&input, Some(DIM * DIM))?.1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_matches_serial() -> miette::Result<()> {
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+        let map = Map::new(input)?;
+        let mut traced = map.clone();
+        traced.trace_path();
+
+        let mut serial = 0;
+        for location in candidate_locations(&map, &traced) {
+            if map.creates_loop(location) {
+                serial += 1;
+            }
+        }
+
+        assert_eq!(serial.to_string(), process(input)?.1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_jump_matches_stepwise() -> miette::Result<()> {
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+        let mut jumped = Map::new(input)?;
+        assert_eq!(Outcome::Exited, jumped.trace_path());
+        let mut stepped = Map::new(input)?;
+        assert_eq!(Outcome::Exited, stepped.track_path()?);
+        assert_eq!(stepped.path, jumped.path);
+
+        let mut jump_loops = process(input)?.0;
+        let mut step_loops = process_stepwise(input)?.0;
+        jump_loops.sort();
+        step_loops.sort();
+        assert_eq!(step_loops, jump_loops);
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_stop() -> miette::Result<()> {
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+        let map = Map::new(input)?;
+        let start = *map.guard_location();
+
+        assert_eq!(
+            Some(Location { x: 4, y: 1 }),
+            map.next_stop(start, &Direction::North)
+        );
+        assert_eq!(
+            Some(Location { x: 2, y: 6 }),
+            map.next_stop(start, &Direction::West)
+        );
+        assert_eq!(None, map.next_stop(start, &Direction::East));
+        assert_eq!(None, map.next_stop(start, &Direction::South));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_border() -> miette::Result<()> {
+        // The guard starts on the bottom row and leaves through row 0
+        let input = "......
+......
+...#..
+.#...#
+....#.
+#..^..";
+
+        let mut map = Map::new(input)?;
+        assert_eq!(Outcome::Exited, map.track_path()?);
+        assert!(map.path.iter().any(|entry| entry.location.y == 0));
+
+        let (mut locations, count) = process(input)?;
+        locations.sort();
+        assert_eq!(
+            vec![Location { x: 2, y: 2 }, Location { x: 2, y: 3 }],
+            locations
+        );
+        assert_eq!("2", count);
+        assert_eq!(count, process_stepwise(input)?.1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_char() {
+        assert_eq!(Some(Direction::North), Direction::from_char('^'));
+        assert_eq!(Some(Direction::South), Direction::from_char('v'));
+        assert_eq!(Some(Direction::East), Direction::from_char('>'));
+        assert_eq!(Some(Direction::West), Direction::from_char('<'));
+        assert_eq!(None, Direction::from_char('.'));
+    }
+
+    #[test]
+    fn test_process_guard_facing_east() -> miette::Result<()> {
+        // Facing east, the guard walks straight off the map and no obstacle can trap it
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..>.....
+........#.
+#.........
+......#...";
+        assert_eq!("0", process(input)?.1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_positions() -> miette::Result<()> {
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+        assert_eq!(41, distinct_positions(input)?);
+        assert_eq!(
+            crate::part1::process(input)?,
+            distinct_positions(input)?.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_patrolled_positions() -> miette::Result<()> {
+        // One guard crosses the map left to right, the other bottom to top, and
+        // their paths meet at (2, 1)
+        let input = ".....
+>....
+.....
+..^..";
+
+        let mut map = Map::new(input)?;
+        assert_eq!(2, map.guards.len());
+        assert_eq!(vec![Outcome::Exited; 2], map.track_paths()?);
+
+        assert_eq!(5, distinct_positions(input)?);
+        assert_eq!(8, patrolled_positions(input)?);
+
+        let single = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+        assert_eq!(distinct_positions(single)?, patrolled_positions(single)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_guard_never_exits() -> miette::Result<()> {
+        let input = ".#..
+...#
+#^..
+..#.";
+
+        let mut jumped = Map::new(input)?;
+        assert!(matches!(jumped.trace_path(), Outcome::Looped(_)));
+        let mut stepped = Map::new(input)?;
+        assert!(matches!(stepped.track_path(), Ok(Outcome::Looped(_))));
+
+        assert_eq!(4, distinct_positions(input)?);
+        assert!(process(input).is_err());
+        assert!(process_stepwise(input).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_points_reject_invalid_maps() {
+        for input in ["...\n...", "..^.\n..\n....", ""] {
+            assert!(process(input).is_err());
+            assert!(process_with_limit(input, Some(100)).is_err());
+            assert!(process_with_directions(input).is_err());
+            assert!(patrolled_positions(input).is_err());
+            assert!(process_stepwise(input).is_err());
+        }
+    }
+
+    #[test]
+    fn test_distinct_positions_invalid_maps() -> miette::Result<()> {
+        // Both part 1 answers reject the same maps
+        for input in ["....\n.#..\n....", "", "\n\n", "..^.\n..\n...."] {
+            assert!(distinct_positions(input).is_err());
+            assert!(crate::part1::process(input).is_err());
+        }
+
+        // Line endings don't matter
+        assert_eq!(2, distinct_positions("...\r\n.^.\r\n...\r\n")?);
+        Ok(())
+    }
 }
@@ -24,6 +24,7 @@ struct ParseLineError {
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let equations: Vec<TestEquation> = input
         .lines()
         .filter_map(|line| {
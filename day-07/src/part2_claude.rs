@@ -12,6 +12,7 @@ type TestEquation = (usize, Vec<usize>);
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     // Pre-allocate with capacity based on rough line count
     let line_count = input.bytes().filter(|&b| b == b'\n').count() + 1;
     let mut equations = Vec::with_capacity(line_count);
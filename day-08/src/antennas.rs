@@ -0,0 +1,668 @@
+use itertools::Itertools;
+use miette::{miette, Diagnostic, SourceSpan};
+use nom::{
+    character::complete::{newline, satisfy},
+    multi::{many1, separated_list1},
+    IResult, Parser,
+};
+use nom_locate::LocatedSpan;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("Failed to parse grid")]
+#[diagnostic(
+    code(day8::parse_error),
+    help("Input must contain only dots (.), digits (0-9), or letters (a-z, A-Z)")
+)]
+struct GridParseError {
+    #[source_code]
+    src: String,
+    #[label("Parse error occurred here")]
+    span: SourceSpan,
+    kind: nom::error::ErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Antinode {
+    pub(crate) x: isize,
+    pub(crate) y: isize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Antenna(Location);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Location {
+    x: usize,
+    y: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Map {
+    xdim: usize,
+    ydim: usize,
+}
+
+/// Antennas grouped by frequency. Frequencies are case-sensitive, so `a` and `A` are
+/// distinct and never pair up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AntennaSet(HashMap<char, Vec<Antenna>>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AntinodeSet(pub(crate) HashSet<Antinode>);
+
+pub(crate) fn parse_input(input: &str) -> miette::Result<(Map, AntennaSet)> {
+    let mut antenna_set = AntennaSet(HashMap::new());
+    let xdim = input.lines().next().map_or(0, str::len);
+    if xdim == 0 {
+        return Err(miette::miette!("Empty map"));
+    }
+    // Rendering and bounds checks both take every row to be as wide as the first
+    if let Some((y, line)) = input
+        .lines()
+        .enumerate()
+        .find(|(_, line)| line.len() != xdim)
+    {
+        return Err(miette::miette!(
+            "Row {} is {} wide, expected {}",
+            y,
+            line.len(),
+            xdim
+        ));
+    }
+    let map = Map {
+        xdim,
+        ydim: input.lines().count(),
+    };
+
+    tracing::debug!("Map dimensions: {}x{}", map.xdim, map.ydim);
+
+    let result = parse_grid(LocatedSpan::new(input));
+
+    match result {
+        Ok((_, result)) => {
+            for c in result.iter().filter(|c| c.character != EMPTY) {
+                antenna_set
+                    .0
+                    .entry(c.character)
+                    .or_default()
+                    .push(Antenna(Location {
+                        // nom_locate positions are 1-based, locations are 0-based
+                        x: c.position.get_column() - 1,
+                        y: c.position.location_line() as usize - 1,
+                    }));
+            }
+            Ok((map, antenna_set))
+        }
+        Err(nom::Err::Error(e)) => {
+            let offset = e.input.location_offset();
+            let err = GridParseError {
+                src: input.to_string(),
+                span: (offset, 1).into(),
+                kind: e.code,
+            };
+            Err(err.into())
+        }
+        Err(e) => {
+            // Handle other error variants (Failure, Incomplete) if needed
+            Err(miette::Error::msg(format!("Parse error: {:?}", e)))
+        }
+    }
+}
+
+/// Collects the antinodes of every same-frequency antenna pair that fall within the map.
+///
+/// Without `harmonic`, each pair produces the two points at the same distance again on
+/// either side of the pair (part 1). With `harmonic`, every grid point in line with the
+/// pair counts, including the antennas themselves (part 2).
+pub(crate) fn calculate_antinodes_with_mode(
+    antennas: &AntennaSet,
+    map: &Map,
+    harmonic: bool,
+) -> miette::Result<AntinodeSet> {
+    let by_frequency = calculate_antinodes_by_frequency(antennas, map, harmonic)?;
+
+    Ok(AntinodeSet(
+        by_frequency
+            .into_values()
+            .flat_map(|antinodes| antinodes.0)
+            .collect(),
+    ))
+}
+
+/// Same antinodes as [`calculate_antinodes_with_mode`], kept apart by the frequency of
+/// the antennas that produce them. A location can turn up under several frequencies.
+pub(crate) fn calculate_antinodes_by_frequency(
+    antennas: &AntennaSet,
+    map: &Map,
+    harmonic: bool,
+) -> miette::Result<HashMap<char, AntinodeSet>> {
+    let mut by_frequency = HashMap::new();
+
+    for (&frequency, antenna_locations) in &antennas.0 {
+        let antinodes = by_frequency
+            .entry(frequency)
+            .or_insert_with(|| AntinodeSet(HashSet::new()));
+
+        for (a, b) in antenna_locations.iter().tuple_combinations() {
+            if harmonic {
+                for antenna in [a, b] {
+                    antinodes.0.insert(Antinode {
+                        x: antenna.0.x as isize,
+                        y: antenna.0.y as isize,
+                    });
+                }
+                antinodes.0.extend(calculate_antinode_vec(a, b, map)?);
+            } else {
+                let (anti_a, anti_b) = calculate_antinode_pair(a, b);
+                antinodes.0.extend(
+                    [anti_a, anti_b]
+                        .into_iter()
+                        .filter(|antinode| bounds_check(antinode, map)),
+                );
+            }
+        }
+    }
+
+    Ok(by_frequency)
+}
+
+/// How many distinct antinodes each frequency produces on its own, before the
+/// frequencies are merged and shared locations only count once
+pub fn antinodes_by_frequency(input: &str, harmonic: bool) -> miette::Result<HashMap<char, usize>> {
+    let input: &str = &grid::normalize(input);
+    let (map, antennas) = parse_input(input)?;
+
+    Ok(calculate_antinodes_by_frequency(&antennas, &map, harmonic)?
+        .into_iter()
+        .map(|(frequency, antinodes)| (frequency, antinodes.0.len()))
+        .collect())
+}
+
+/// Draws the map with its antennas, marking antinodes with `#` wherever no antenna
+/// already sits, as in the puzzle's illustrations.
+pub(crate) fn render_with_mode(input: &str, harmonic: bool) -> miette::Result<String> {
+    let (map, antennas) = parse_input(input)?;
+    let antinodes = calculate_antinodes_with_mode(&antennas, &map, harmonic)?;
+
+    let mut grid = vec![vec![EMPTY; map.xdim]; map.ydim];
+    for (&frequency, locations) in &antennas.0 {
+        for antenna in locations {
+            grid[antenna.0.y][antenna.0.x] = frequency;
+        }
+    }
+    for antinode in &antinodes.0 {
+        let cell = &mut grid[antinode.y as usize][antinode.x as usize];
+        if *cell == EMPTY {
+            *cell = ANTINODE;
+        }
+    }
+
+    Ok(grid
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .join("\n"))
+}
+
+fn calculate_antinode_pair(a: &Antenna, b: &Antenna) -> (Antinode, Antinode) {
+    let rise = b.0.y as isize - a.0.y as isize;
+    let run = b.0.x as isize - a.0.x as isize;
+
+    let antinode_a = Antinode {
+        x: a.0.x as isize - run,
+        y: a.0.y as isize - rise,
+    };
+
+    let antinode_b = Antinode {
+        x: b.0.x as isize + run,
+        y: b.0.y as isize + rise,
+    };
+
+    (antinode_a, antinode_b)
+}
+
+struct Slope {
+    rise: isize,
+    run: isize,
+}
+
+/// Reduces the step from `a` to `b` to its smallest integer form.
+///
+/// Errors when both antennas share a location, since there is no line through them
+/// and stepping by a zero slope would never leave the map.
+fn calculate_slope(a: &Antenna, b: &Antenna) -> miette::Result<Slope> {
+    let rise = b.0.y as isize - a.0.y as isize;
+    let run = b.0.x as isize - a.0.x as isize;
+
+    if rise == 0 && run == 0 {
+        return Err(miette!(
+            "Antennas share the location ({}, {})",
+            a.0.x,
+            a.0.y
+        ));
+    }
+    if run == 0 {
+        return Ok(Slope {
+            rise: rise.signum(),
+            run: 0,
+        });
+    }
+    if rise == 0 {
+        return Ok(Slope {
+            rise: 0,
+            run: run.signum(),
+        });
+    }
+
+    let gcd = gcd(rise.abs(), run.abs());
+    Ok(Slope {
+        rise: rise / gcd,
+        run: run / gcd,
+    })
+}
+
+fn gcd(mut a: isize, mut b: isize) -> isize {
+    while b != 0 {
+        let temp = b;
+        b = a % b;
+        a = temp;
+    }
+    a
+}
+
+fn calculate_antinode_vec(a: &Antenna, b: &Antenna, map: &Map) -> miette::Result<Vec<Antinode>> {
+    let slope = calculate_slope(a, b)?;
+    let mut antinodes = Vec::new();
+
+    antinodes_a(map, &slope, a, 1, &mut antinodes)?;
+    antinodes_b(map, &slope, b, 1, &mut antinodes)?;
+
+    Ok(antinodes)
+}
+
+fn antinodes_a(
+    map: &Map,
+    slope: &Slope,
+    antenna: &Antenna,
+    scalar: isize,
+    antinodes: &mut Vec<Antinode>,
+) -> miette::Result<()> {
+    let antinode = Antinode {
+        x: antenna.0.x as isize - scalar * slope.run,
+        y: antenna.0.y as isize - scalar * slope.rise,
+    };
+
+    if bounds_check(&antinode, map) {
+        antinodes.push(antinode);
+
+        antinodes_a(map, slope, antenna, scalar + 1, antinodes)?;
+    }
+
+    Ok(())
+}
+fn antinodes_b(
+    map: &Map,
+    slope: &Slope,
+    antenna: &Antenna,
+    scalar: isize,
+    antinodes: &mut Vec<Antinode>,
+) -> miette::Result<()> {
+    let antinode = Antinode {
+        x: antenna.0.x as isize + scalar * slope.run,
+        y: antenna.0.y as isize + scalar * slope.rise,
+    };
+
+    if bounds_check(&antinode, map) {
+        antinodes.push(antinode);
+
+        antinodes_b(map, slope, antenna, scalar + 1, antinodes)?;
+    }
+
+    Ok(())
+}
+
+fn bounds_check(antinode: &Antinode, map: &Map) -> bool {
+    antinode.x >= 0
+        && antinode.y >= 0
+        && antinode.x < map.xdim as isize
+        && antinode.y < map.ydim as isize
+}
+
+// region: nom parser
+const EMPTY: char = '.';
+const ANTINODE: char = '#';
+
+type CharSpan<'a> = LocatedSpan<&'a str>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct LocatedChar<'a> {
+    character: char,
+    position: CharSpan<'a>,
+}
+
+/// Matches an empty cell or an antenna, whose frequency is any ASCII letter or digit.
+fn parse_alphanumeric(input: CharSpan) -> IResult<CharSpan, LocatedChar> {
+    satisfy(|c: char| c.is_ascii_alphanumeric() || c == EMPTY)
+        .map(|c| LocatedChar {
+            character: c,
+            position: input,
+        })
+        .parse(input)
+}
+
+fn parse_grid(input: CharSpan) -> IResult<CharSpan, Vec<LocatedChar>> {
+    let (input, lines) = separated_list1(newline, many1(parse_alphanumeric))(input)?;
+    Ok((input, lines.into_iter().flatten().collect()))
+}
+// endregion
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test_log::test]
+    fn test_parse_grid() -> miette::Result<()> {
+        let input = LocatedSpan::new(
+            "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............",
+        );
+
+        let result = parse_grid(input);
+        let mut antenna_count = 0;
+        match &result {
+            Ok((_, result)) => {
+                for c in result.iter() {
+                    if c.character != EMPTY {
+                        antenna_count += 1;
+                        tracing::debug!(
+                            "char: {}, line: {}, col: {}",
+                            c.character,
+                            c.position.location_line(),
+                            c.position.get_column()
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                dbg!(e);
+            }
+        }
+
+        assert_eq!(antenna_count, 7);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_parse_grid_small() -> miette::Result<()> {
+        let input = LocatedSpan::new(
+            "...\n.0.\n..A", // Simplified 3x3 grid for clear testing
+        );
+
+        match parse_grid(input) {
+            Ok((_, chars)) => {
+                // Total length should be 9 (3x3 grid)
+                assert_eq!(chars.len(), 9);
+
+                // Check some specific positions
+                let zero = chars.iter().find(|c| c.character == '0').unwrap();
+                let a = chars.iter().find(|c| c.character == 'A').unwrap();
+
+                // Zero should be on line 2, position 1
+                assert_eq!(zero.position.location_line(), 2);
+                assert_eq!(zero.position.get_column(), 2);
+
+                // 'A' should be on line 3, position 2
+                assert_eq!(a.position.location_line(), 3);
+                assert_eq!(a.position.get_column(), 3);
+
+                // Count non-empty characters
+                let non_empty = chars.iter().filter(|c| c.character != '.').count();
+                assert_eq!(non_empty, 2);
+            }
+            Err(e) => {
+                panic!("Failed to parse grid: {:?}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_parse_input() -> miette::Result<()> {
+        let input = "...\n.0.\n..A";
+        let (map, antennas) = parse_input(input).unwrap();
+
+        assert_eq!(map.xdim, 3);
+        assert_eq!(map.ydim, 3);
+        assert_eq!(antennas.0.len(), 2);
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_calculate_slope() -> miette::Result<()> {
+        let a = Antenna(Location { x: 0, y: 0 });
+        let b = Antenna(Location { x: 3, y: 4 });
+        let slope = calculate_slope(&a, &b)?;
+        assert_eq!((slope.rise, slope.run), (4, 3));
+
+        // negative slope
+        let a = Antenna(Location { x: 0, y: 4 });
+        let b = Antenna(Location { x: 3, y: 0 });
+        let slope = calculate_slope(&a, &b)?;
+        assert_eq!((slope.rise, slope.run), (-4, 3));
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_calculate_antinode_pair() -> miette::Result<()> {
+        let expected_antinodes = (Antinode { x: 0, y: 0 }, Antinode { x: 3, y: 3 });
+
+        let antinode_pair = calculate_antinode_pair(
+            &Antenna(Location { x: 1, y: 1 }),
+            &Antenna(Location { x: 2, y: 2 }),
+        );
+
+        assert_eq!(antinode_pair, expected_antinodes);
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_calculate_antinode_vec() -> miette::Result<()> {
+        let expected_antinodes = [Antinode { x: 0, y: 0 }, Antinode { x: 3, y: 3 }];
+        let map = Map { xdim: 4, ydim: 4 };
+        let antinode_pair = calculate_antinode_vec(
+            &Antenna(Location { x: 1, y: 1 }),
+            &Antenna(Location { x: 2, y: 2 }),
+            &map,
+        )
+        .unwrap();
+
+        assert_eq!(antinode_pair, expected_antinodes);
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_calculate_antinodes_harmonic() -> miette::Result<()> {
+        let antennas = HashMap::from([(
+            'A',
+            vec![
+                Antenna(Location { x: 1, y: 1 }),
+                Antenna(Location { x: 2, y: 2 }),
+            ],
+        )]);
+
+        let expected_antinodes = HashSet::from([
+            Antinode { x: 0, y: 0 },
+            Antinode { x: 1, y: 1 },
+            Antinode { x: 2, y: 2 },
+            Antinode { x: 3, y: 3 },
+        ]);
+
+        let map = Map { xdim: 4, ydim: 4 };
+
+        let antinodes = calculate_antinodes_with_mode(&AntennaSet(antennas), &map, true)?;
+
+        assert_eq!(antinodes.0, expected_antinodes);
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_bounds_check() -> miette::Result<()> {
+        let map = Map { xdim: 3, ydim: 3 };
+        let antinode = Antinode { x: 0, y: 0 };
+        assert!(bounds_check(&antinode, &map));
+
+        let antinode = Antinode { x: 2, y: 2 };
+        assert!(bounds_check(&antinode, &map));
+
+        let antinode = Antinode { x: 3, y: 3 };
+        assert!(!bounds_check(&antinode, &map));
+
+        let antinode = Antinode { x: -1, y: 0 };
+        assert!(!bounds_check(&antinode, &map));
+
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_parse_input_zero_based() -> miette::Result<()> {
+        let input = "...\n.0.\n..A";
+        let (_map, antennas) = parse_input(input)?;
+
+        assert_eq!(vec![Antenna(Location { x: 1, y: 1 })], antennas.0[&'0']);
+        assert_eq!(vec![Antenna(Location { x: 2, y: 2 })], antennas.0[&'A']);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_input_rejects_ragged_rows() {
+        assert!(parse_input("..\n...0\n.0.").is_err());
+        assert!(parse_input("...\n.0\n...").is_err());
+        assert!(parse_input("").is_err());
+        assert!(render_with_mode("..\n...0\n.0.", false).is_err());
+    }
+
+    #[test_log::test]
+    fn test_antinode_on_grid_edge() -> miette::Result<()> {
+        // The pair's antinodes land at (1, 1) and on the bottom-right corner (4, 4)
+        let input = ".....\n.....\n..a..\n...a.\n.....";
+        let (map, antennas) = parse_input(input)?;
+
+        let antinodes = calculate_antinodes_with_mode(&antennas, &map, false)?;
+        assert_eq!(
+            HashSet::from([Antinode { x: 1, y: 1 }, Antinode { x: 4, y: 4 }]),
+            antinodes.0
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_calculate_antinodes_with_mode() -> miette::Result<()> {
+        let input = "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+        let (map, antennas) = parse_input(input)?;
+
+        let antinodes = calculate_antinodes_with_mode(&antennas, &map, false)?;
+        assert_eq!(14, antinodes.0.len());
+
+        let antinodes = calculate_antinodes_with_mode(&antennas, &map, true)?;
+        assert_eq!(34, antinodes.0.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_antinodes_by_frequency() -> miette::Result<()> {
+        let input = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+        // One antinode is shared, so 10 + 5 is one more than the 14 overall
+        let counts = antinodes_by_frequency(input, false)?;
+        assert_eq!(HashMap::from([('0', 10), ('A', 5)]), counts);
+
+        // 21 + 16 against 34 overall with harmonics
+        let counts = antinodes_by_frequency(input, true)?;
+        assert_eq!(HashMap::from([('0', 21), ('A', 16)]), counts);
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_frequencies_are_case_sensitive() -> miette::Result<()> {
+        // Pairing an 'a' with an 'A' would add antinodes such as (0, 2) and (6, 2)
+        let input = ".......
+.......
+..a.A..
+.......
+..A.a..
+.......
+.......";
+        let (map, antennas) = parse_input(input)?;
+
+        assert_eq!(2, antennas.0.len());
+        assert_eq!(2, antennas.0[&'a'].len());
+        assert_eq!(2, antennas.0[&'A'].len());
+
+        let antinodes = calculate_antinodes_with_mode(&antennas, &map, false)?;
+        assert_eq!(
+            HashSet::from([
+                Antinode { x: 0, y: 0 },
+                Antinode { x: 6, y: 6 },
+                Antinode { x: 6, y: 0 },
+                Antinode { x: 0, y: 6 },
+            ]),
+            antinodes.0
+        );
+        Ok(())
+    }
+
+    #[test_log::test]
+    fn test_coincident_antennas() -> miette::Result<()> {
+        let a = Antenna(Location { x: 2, y: 2 });
+        assert!(calculate_slope(&a, &a).is_err());
+
+        let map = Map { xdim: 4, ydim: 4 };
+        let antennas = AntennaSet(HashMap::from([('a', vec![a, a])]));
+        assert!(calculate_antinodes_with_mode(&antennas, &map, true).is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,4 @@
-use day_09::part1::{process, DiskState};
-use day_09::part2::*;
+use day_09::part1::DiskState;
 
 fn main() {
     divan::main();
@@ -0,0 +1,263 @@
+use itertools::Itertools;
+use miette::{miette, Result};
+use miette::{Diagnostic, SourceSpan};
+use thiserror::Error;
+
+// region: miette error handling
+#[derive(Debug, Error, Diagnostic)]
+#[error("Invalid character in input")]
+#[diagnostic(code(parse::invalid_char), help("Input must contain only digits 0-9"))]
+struct InvalidCharError {
+    #[source_code]
+    src: String,
+
+    #[label("invalid character found here")]
+    span: SourceSpan,
+
+    character: char,
+}
+
+impl InvalidCharError {
+    fn new(input: &str, pos: usize, c: char) -> Self {
+        let start = pos.saturating_sub(5);
+        let end = (pos + 1).min(input.len());
+        let context_end = (pos + 6).min(input.len());
+
+        Self {
+            src: input[start..context_end].to_string(),
+            span: ((pos - start)..(end - start)).into(),
+            character: c,
+        }
+    }
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("Invalid block size")]
+#[diagnostic(
+    code(parse::invalid_block_size),
+    help("Block size must be a single digit 0-9")
+)]
+struct InvalidBlockSizeError {
+    #[source_code]
+    src: String,
+
+    #[label("invalid block size")]
+    span: SourceSpan,
+
+    digit: char,
+}
+
+impl InvalidBlockSizeError {
+    fn new(input: &str, pos: usize, digit: char) -> Self {
+        let start = pos.saturating_sub(5);
+        let end = (pos + 1).min(input.len());
+        let context_end = (pos + 6).min(input.len());
+
+        Self {
+            src: input[start..context_end].to_string(),
+            span: ((pos - start)..(end - start)).into(),
+            digit,
+        }
+    }
+}
+
+// endregion
+
+/// The disk map decoded into runs, without committing to any block representation.
+/// Files are `(id, len, start)` and free runs `(len, start)`, both in disk order;
+/// zero-length gaps are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Layout {
+    pub files: Vec<(usize, usize, usize)>,
+    pub free: Vec<(usize, usize)>,
+}
+
+impl Layout {
+    /// Number of blocks on the disk, files and free space alike
+    pub fn len(&self) -> usize {
+        let file_end = self.files.iter().map(|&(_, len, start)| start + len);
+        let free_end = self.free.iter().map(|&(len, start)| start + len);
+        file_end.chain(free_end).max().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Decodes a disk map of alternating file and free-space sizes, e.g. `12345`
+pub fn parse_layout(input: &str) -> Result<Layout> {
+    if input.is_empty() {
+        return Err(miette!("Empty input"));
+    }
+
+    // Find non-digit characters with their positions
+    if let Some((pos, c)) = input.chars().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
+        return Err(InvalidCharError::new(input, pos, c).into());
+    }
+
+    let mut layout = Layout::default();
+    let mut start = 0;
+
+    // Parse alternating digits as file sizes and free spaces
+    for (i, size_char) in input.chars().enumerate() {
+        let len = size_char
+            .to_digit(10)
+            .ok_or_else(|| InvalidBlockSizeError::new(input, i, size_char))?
+            as usize;
+
+        if i % 2 == 0 {
+            layout.files.push((i / 2, len, start));
+        } else if len > 0 {
+            layout.free.push((len, start));
+        }
+        start += len;
+    }
+
+    // A map of nothing but zero sizes leaves nothing to pack or checksum
+    if layout.is_empty() {
+        return Err(miette!("Disk map describes no blocks"));
+    }
+
+    Ok(layout)
+}
+
+/// Views shared by both parts' disks, built on the block-by-block [`layout`](Self::layout)
+/// each part supplies.
+pub trait BlockLayout {
+    /// Yields `Some(file_id)` or `None` (free space) for every block on the disk, in order.
+    fn layout(&self) -> impl DoubleEndedIterator<Item = Option<usize>> + Clone + '_;
+
+    /// The `Display` layout cut down to at most `max_len` characters, see
+    /// [`render_compact`].
+    fn render_compact(&self, max_len: usize) -> String {
+        render_compact(self.layout(), max_len)
+    }
+
+    /// Number of file fragments, i.e. maximal runs of blocks sharing the same file id.
+    fn fragmentation(&self) -> usize {
+        self.layout().dedup().flatten().count()
+    }
+
+    /// True when every file sits in one unbroken run of blocks, as whole-file
+    /// compaction leaves them.
+    fn is_contiguous(&self) -> bool {
+        self.layout().dedup().flatten().all_unique()
+    }
+}
+
+/// Renders blocks as `Display` does, but never longer than `max_len` characters. A
+/// disk that doesn't fit shows as many whole leading file blocks as there is room for,
+/// then `...` and a count of the free run at the end, e.g. `00998111...(14 free)`. The
+/// count is left out when there is no free tail or no room for it.
+pub fn render_compact<I>(blocks: I, max_len: usize) -> String
+where
+    I: DoubleEndedIterator<Item = Option<usize>> + Clone,
+{
+    let render = |block: Option<usize>| block.map_or(".".to_string(), |id| id.to_string());
+
+    let mut full = String::new();
+    for block in blocks.clone() {
+        full.push_str(&render(block));
+        if full.len() > max_len {
+            break;
+        }
+    }
+    if full.len() <= max_len {
+        return full;
+    }
+
+    let free = blocks.clone().rev().take_while(Option::is_none).count();
+    let summary = Some(format!("...({} free)", free))
+        .filter(|summary| free > 0 && summary.len() <= max_len)
+        .unwrap_or_else(|| "...".to_string());
+    let budget = max_len.saturating_sub(summary.len());
+
+    let head_len = blocks.clone().count() - free;
+    let mut head = String::new();
+    for block in blocks.take(head_len).map(render) {
+        if head.len() + block.len() > budget {
+            break;
+        }
+        head.push_str(&block);
+    }
+
+    let mut compact = head + &summary;
+    compact.truncate(max_len);
+    compact
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_layout() -> Result<()> {
+        // 0..111....22222
+        let layout = parse_layout("12345")?;
+        assert_eq!(vec![(0, 1, 0), (1, 3, 3), (2, 5, 10)], layout.files);
+        assert_eq!(vec![(2, 1), (4, 6)], layout.free);
+        assert_eq!(15, layout.len());
+
+        // Empty files keep their id, empty gaps are dropped
+        let layout = parse_layout("10201")?;
+        assert_eq!(vec![(0, 1, 0), (1, 2, 1), (2, 1, 3)], layout.files);
+        assert!(layout.free.is_empty());
+
+        assert!(parse_layout("").is_err());
+        assert!(parse_layout("00").is_err());
+        assert!(parse_layout("1x2").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_compact() {
+        // 0099811188827773336446555566..............
+        let packed = "0099811188827773336446555566"
+            .chars()
+            .map(|c| c.to_digit(10).map(|id| id as usize))
+            .chain(std::iter::repeat_n(None, 14));
+
+        let full = render_compact(packed.clone(), 42);
+        assert_eq!("0099811188827773336446555566..............", full);
+        assert_eq!(full, render_compact(packed.clone(), 100));
+
+        let compact = render_compact(packed.clone(), 20);
+        assert_eq!("00998111...(14 free)", compact);
+
+        // Room for every file block still ends with the summary, not the dots
+        assert_eq!(
+            "0099811188827773336446555566...(14 free)",
+            render_compact(packed, 41)
+        );
+    }
+
+    #[test]
+    fn test_render_compact_multi_digit_ids() {
+        let blocks = [Some(10), Some(11), Some(12)]
+            .into_iter()
+            .chain(std::iter::repeat_n(None, 100));
+
+        // Ids are never split, so 12 doesn't fit beside the summary
+        assert_eq!("1011...(100 free)", render_compact(blocks.clone(), 18));
+        assert_eq!("101112...(100 free)", render_compact(blocks, 20));
+    }
+
+    #[test]
+    fn test_render_compact_no_free_tail() {
+        let blocks = (0..30).map(Some);
+        assert_eq!("0123456...", render_compact(blocks.clone(), 10));
+        assert_eq!("0123456789...", render_compact(blocks, 14));
+    }
+
+    #[test]
+    fn test_render_compact_tiny_max_len() {
+        let blocks = [Some(0), Some(1), Some(2)]
+            .into_iter()
+            .chain(std::iter::repeat_n(None, 100));
+
+        assert_eq!("01...", render_compact(blocks.clone(), 5));
+        assert_eq!("..", render_compact(blocks.clone(), 2));
+        assert_eq!("", render_compact(blocks, 0));
+    }
+}
@@ -1,66 +1,7 @@
+use crate::layout::{parse_layout, BlockLayout};
 use miette::{miette, Result};
-use miette::{Diagnostic, SourceSpan};
-use thiserror::Error;
+use std::path::Path;
 
-// region: miette error handling
-#[derive(Debug, Error, Diagnostic)]
-#[error("Invalid character in input")]
-#[diagnostic(code(parse::invalid_char), help("Input must contain only digits 0-9"))]
-struct InvalidCharError {
-    #[source_code]
-    src: String,
-
-    #[label("invalid character found here")]
-    span: SourceSpan,
-
-    character: char,
-}
-
-impl InvalidCharError {
-    fn new(input: &str, pos: usize, c: char) -> Self {
-        let start = pos.saturating_sub(5);
-        let end = (pos + 1).min(input.len());
-        let context_end = (pos + 6).min(input.len());
-
-        Self {
-            src: input[start..context_end].to_string(),
-            span: ((pos - start)..(end - start)).into(),
-            character: c,
-        }
-    }
-}
-
-#[derive(Debug, Error, Diagnostic)]
-#[error("Invalid block size")]
-#[diagnostic(
-    code(parse::invalid_block_size),
-    help("Block size must be a single digit 0-9")
-)]
-struct InvalidBlockSizeError {
-    #[source_code]
-    src: String,
-
-    #[label("invalid block size")]
-    span: SourceSpan,
-
-    digit: char,
-}
-
-impl InvalidBlockSizeError {
-    fn new(input: &str, pos: usize, digit: char) -> Self {
-        let start = pos.saturating_sub(5);
-        let end = (pos + 1).min(input.len());
-        let context_end = (pos + 6).min(input.len());
-
-        Self {
-            src: input[start..context_end].to_string(),
-            span: ((pos - start)..(end - start)).into(),
-            digit,
-        }
-    }
-}
-
-// endregion
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct FileBlock {
     id: usize,
@@ -76,37 +17,11 @@ pub struct DiskState {
 
 impl DiskState {
     pub fn new(input: &str) -> Result<Self> {
-        if input.is_empty() {
-            return Err(miette!("Empty input"));
-        }
+        let layout = parse_layout(input)?;
 
-        // Find non-digit characters with their positions
-        if let Some((pos, c)) = input.chars().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
-            return Err(InvalidCharError::new(input, pos, c).into());
-        }
-
-        let mut blocks = Vec::new();
-        let mut file_id = 0;
-
-        // Parse alternating digits as file sizes and free spaces
-        for (i, size_char) in input.chars().enumerate() {
-            let size = size_char
-                .to_digit(10)
-                .ok_or_else(|| InvalidBlockSizeError::new(input, i, size_char))?
-                as usize;
-
-            if i % 2 == 0 {
-                // File blocks
-                for _ in 0..size {
-                    blocks.push(Some(FileBlock { id: file_id, size }));
-                }
-                file_id += 1;
-            } else {
-                // Free space
-                for _ in 0..size {
-                    blocks.push(None);
-                }
-            }
+        let mut blocks = vec![None; layout.len()];
+        for (id, size, start) in layout.files {
+            blocks[start..start + size].fill(Some(FileBlock { id, size }));
         }
 
         Ok(Self { blocks })
@@ -147,9 +62,13 @@ impl DiskState {
 
         Ok(())
     }
-    // Helper method for debugging
-    fn debug_state(&self) -> String {
-        format!("{}", self)
+    /// Writes the current disk layout (as rendered by `Display`) to `path`.
+    ///
+    /// This is opt-in so that `process` never touches the filesystem.
+    pub fn dump_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string())
+            .map_err(|e| miette!("Failed to write {}: {}", path.display(), e))
     }
 
     pub fn checksum(&self) -> Result<u64> {
@@ -172,6 +91,14 @@ impl DiskState {
     }
 }
 
+impl BlockLayout for DiskState {
+    fn layout(&self) -> impl DoubleEndedIterator<Item = Option<usize>> + Clone + '_ {
+        self.blocks
+            .iter()
+            .map(|maybe_block| maybe_block.as_ref().map(|block| block.id))
+    }
+}
+
 impl std::fmt::Display for DiskState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for block in &self.blocks {
@@ -186,9 +113,15 @@ impl std::fmt::Display for DiskState {
 
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String> {
+    Ok(process_typed(input)?.to_string())
+}
+
+/// The filesystem checksum once the disk is packed
+pub fn process_typed(input: &str) -> Result<u64> {
+    let input: &str = &grid::normalize(input);
     let mut disk_state = DiskState::new(input.trim())?;
     disk_state.pack()?;
-    Ok(disk_state.checksum()?.to_string())
+    disk_state.checksum()
 }
 
 #[cfg(test)]
@@ -210,6 +143,47 @@ mod tests {
         Ok(())
     }
 
+    #[test_log::test]
+    fn test_process_multi_digit_ids() -> Result<()> {
+        // 15 files, so ids 10..=14 must contribute their full value to the checksum
+        let input = "12121212121212121212121212123";
+        assert_eq!("997", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_checksum() -> Result<()> {
+        let mut disk_state = DiskState::new("2333133121414131402")?;
+        disk_state.pack()?;
+
+        let checksum: u64 = disk_state
+            .layout()
+            .enumerate()
+            .filter_map(|(pos, id)| id.map(|id| (pos * id) as u64))
+            .sum();
+        assert_eq!(disk_state.checksum()?, checksum);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fragmentation() -> Result<()> {
+        let mut disk_state = DiskState::new("12345")?;
+        assert_eq!(3, disk_state.fragmentation());
+        disk_state.pack()?;
+        // 022111222......
+        assert_eq!(4, disk_state.fragmentation());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_compact() -> Result<()> {
+        let mut disk_state = DiskState::new("12345")?;
+        disk_state.pack()?;
+        assert_eq!("022111222......", disk_state.render_compact(15));
+        assert_eq!("022...(6 free)", disk_state.render_compact(14));
+        Ok(())
+    }
+
     #[test]
     fn test_disk_state_parser() -> Result<()> {
         let input = "12345";
@@ -258,6 +232,61 @@ mod tests {
         Ok(())
     }
 
+    /// Straightforward reference packing: repeatedly move the last file block into
+    /// the first gap, rescanning the whole disk every time.
+    fn naive_checksum(input: &str) -> u64 {
+        let mut disk: Vec<Option<usize>> = input
+            .chars()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                let size = c.to_digit(10).unwrap() as usize;
+                std::iter::repeat_n((i % 2 == 0).then_some(i / 2), size)
+            })
+            .collect();
+
+        loop {
+            let gap = disk.iter().position(Option::is_none);
+            let last = disk.iter().rposition(Option::is_some);
+            match (gap, last) {
+                (Some(gap), Some(last)) if gap < last => disk.swap(gap, last),
+                _ => break,
+            }
+        }
+
+        disk.iter()
+            .enumerate()
+            .filter_map(|(pos, id)| id.map(|id| (pos * id) as u64))
+            .sum()
+    }
+
+    #[test]
+    fn test_pack_matches_naive() -> Result<()> {
+        for input in [
+            "2333133121414131402",
+            "12345",
+            "12121212121212121212121212123",
+        ] {
+            let mut disk_state = DiskState::new(input)?;
+            disk_state.pack()?;
+            assert_eq!(naive_checksum(input), disk_state.checksum()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_to() -> Result<()> {
+        let mut disk_state = DiskState::new("12345")?;
+        disk_state.pack()?;
+
+        let path = std::env::temp_dir().join("day-09-dump_to.txt");
+        disk_state.dump_to(&path)?;
+        let dumped = std::fs::read_to_string(&path).map_err(|e| miette!(e))?;
+        std::fs::remove_file(&path).map_err(|e| miette!(e))?;
+
+        assert_eq!("022111222......", dumped);
+        Ok(())
+    }
+
     #[test_log::test]
     fn test_parser_invalid_input() -> Result<()> {
         let input = "123A45";
@@ -271,4 +300,14 @@ mod tests {
         assert!(DiskState::new(input).is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_process_degenerate_input() -> Result<()> {
+        assert!(process("").is_err());
+        assert!(process(" \n").is_err());
+        assert!(process("0").is_err());
+        // A lone file sits at position 0, so contributes nothing
+        assert_eq!("0", process("1")?);
+        Ok(())
+    }
 }
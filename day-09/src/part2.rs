@@ -1,66 +1,6 @@
+use crate::layout::{parse_layout, BlockLayout};
 use miette::{miette, Result};
-use miette::{Diagnostic, SourceSpan};
-use thiserror::Error;
 
-// region: miette error handling
-#[derive(Debug, Error, Diagnostic)]
-#[error("Invalid character in input")]
-#[diagnostic(code(parse::invalid_char), help("Input must contain only digits 0-9"))]
-struct InvalidCharError {
-    #[source_code]
-    src: String,
-
-    #[label("invalid character found here")]
-    span: SourceSpan,
-
-    character: char,
-}
-
-impl InvalidCharError {
-    fn new(input: &str, pos: usize, c: char) -> Self {
-        let start = pos.saturating_sub(5);
-        let end = (pos + 1).min(input.len());
-        let context_end = (pos + 6).min(input.len());
-
-        Self {
-            src: input[start..context_end].to_string(),
-            span: ((pos - start)..(end - start)).into(),
-            character: c,
-        }
-    }
-}
-
-#[derive(Debug, Error, Diagnostic)]
-#[error("Invalid block size")]
-#[diagnostic(
-    code(parse::invalid_block_size),
-    help("Block size must be a single digit 0-9")
-)]
-struct InvalidBlockSizeError {
-    #[source_code]
-    src: String,
-
-    #[label("invalid block size")]
-    span: SourceSpan,
-
-    digit: char,
-}
-
-impl InvalidBlockSizeError {
-    fn new(input: &str, pos: usize, digit: char) -> Self {
-        let start = pos.saturating_sub(5);
-        let end = (pos + 1).min(input.len());
-        let context_end = (pos + 6).min(input.len());
-
-        Self {
-            src: input[start..context_end].to_string(),
-            span: ((pos - start)..(end - start)).into(),
-            digit,
-        }
-    }
-}
-
-// endregion
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct FileBlock {
     id: usize,
@@ -76,37 +16,11 @@ pub struct DiskState {
 
 impl DiskState {
     pub fn new(input: &str) -> Result<Self> {
-        if input.is_empty() {
-            return Err(miette!("Empty input"));
-        }
+        let layout = parse_layout(input)?;
 
-        // Find non-digit characters with their positions
-        if let Some((pos, c)) = input.chars().enumerate().find(|(_, c)| !c.is_ascii_digit()) {
-            return Err(InvalidCharError::new(input, pos, c).into());
-        }
-
-        let mut blocks = Vec::new();
-        let mut file_id = 0;
-
-        // Parse alternating digits as file sizes and free spaces
-        for (i, size_char) in input.chars().enumerate() {
-            let size = size_char
-                .to_digit(10)
-                .ok_or_else(|| InvalidBlockSizeError::new(input, i, size_char))?
-                as usize;
-
-            if i % 2 == 0 {
-                // File blocks
-                for _ in 0..size {
-                    blocks.push(Some(FileBlock { id: file_id, size }));
-                }
-                file_id += 1;
-            } else {
-                // Free space
-                for _ in 0..size {
-                    blocks.push(None);
-                }
-            }
+        let mut blocks = vec![None; layout.len()];
+        for (id, size, start) in layout.files {
+            blocks[start..start + size].fill(Some(FileBlock { id, size }));
         }
 
         Ok(Self { blocks })
@@ -198,6 +112,14 @@ impl DiskState {
     }
 }
 
+impl BlockLayout for DiskState {
+    fn layout(&self) -> impl DoubleEndedIterator<Item = Option<usize>> + Clone + '_ {
+        self.blocks
+            .iter()
+            .map(|maybe_block| maybe_block.as_ref().map(|block| block.id))
+    }
+}
+
 impl std::fmt::Display for DiskState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for block in &self.blocks {
@@ -212,9 +134,15 @@ impl std::fmt::Display for DiskState {
 
 #[tracing::instrument]
 pub fn process(input: &str) -> Result<String> {
+    Ok(process_typed(input)?.to_string())
+}
+
+/// The filesystem checksum once the disk is packed
+pub fn process_typed(input: &str) -> Result<u64> {
+    let input: &str = &grid::normalize(input);
     let mut disk_state = DiskState::new(input.trim())?;
     disk_state.pack()?;
-    Ok(disk_state.checksum()?.to_string())
+    disk_state.checksum()
 }
 
 #[cfg(test)]
@@ -236,6 +164,57 @@ mod tests {
         Ok(())
     }
 
+    #[test_log::test]
+    fn test_process_multi_digit_ids() -> Result<()> {
+        // 15 files, so ids 10..=14 must contribute their full value to the checksum
+        let input = "12121212121212121212121212123";
+        assert_eq!("2355", process(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_checksum() -> Result<()> {
+        let mut disk_state = DiskState::new("2333133121414131402")?;
+        disk_state.pack()?;
+
+        let checksum: u64 = disk_state
+            .layout()
+            .enumerate()
+            .filter_map(|(pos, id)| id.map(|id| (pos * id) as u64))
+            .sum();
+        assert_eq!(disk_state.checksum()?, checksum);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fragmentation_vs_part1() -> Result<()> {
+        for input in ["2333133121414131402", "12345"] {
+            let mut fragmented = crate::part1::DiskState::new(input)?;
+            fragmented.pack()?;
+            let mut contiguous = DiskState::new(input)?;
+            contiguous.pack()?;
+
+            assert!(fragmented.fragmentation() > contiguous.fragmentation());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_contiguous() -> Result<()> {
+        let input = "2333133121414131402";
+
+        let mut fragmented = crate::part1::DiskState::new(input)?;
+        assert!(fragmented.is_contiguous());
+        fragmented.pack()?;
+        // 0099811188827773336446555566.............. splits files 8 and 6, among others
+        assert!(!fragmented.is_contiguous());
+
+        let mut contiguous = DiskState::new(input)?;
+        contiguous.pack()?;
+        assert!(contiguous.is_contiguous());
+        Ok(())
+    }
+
     #[test]
     fn test_disk_state_parser() -> Result<()> {
         let input = "12345";
@@ -297,4 +276,14 @@ mod tests {
         assert!(DiskState::new(input).is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_process_degenerate_input() -> Result<()> {
+        assert!(process("").is_err());
+        assert!(process(" \n").is_err());
+        assert!(process("0").is_err());
+        // A lone file sits at position 0, so contributes nothing
+        assert_eq!("0", process("1")?);
+        Ok(())
+    }
 }
@@ -0,0 +1,492 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use miette::{miette, Context, Result};
+use nom::{
+    character::complete::{newline, satisfy},
+    multi::{many1, separated_list1},
+    IResult, Parser,
+};
+use nom_locate::LocatedSpan;
+use petgraph::graph::{DiGraph, NodeIndex};
+use tracing::debug;
+
+mod constants {
+    pub const TRAILHEAD: u8 = 0;
+    pub const PEAK: u8 = 9;
+    pub const MAX_VALUE: u8 = PEAK;
+}
+
+use constants::*;
+
+/// Represents a node in the climbing grid with position and height value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Node {
+    x: usize,
+    y: usize,
+    value: u8,
+}
+
+/// Represents the climbing grid with dimensions and node values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    grid: Vec<Vec<Node>>,
+    xdim: usize,
+    ydim: usize,
+}
+
+impl Node {
+    /// Grid coordinates as `(x, y)`
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+}
+
+impl Map {
+    fn add_node(&mut self, node: Node) {
+        self.grid[node.y][node.x] = node;
+    }
+
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.xdim, self.ydim)
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.grid {
+            for node in row {
+                write!(f, "{}", node.value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Map> {
+    // Input validation
+    let xdim = input
+        .lines()
+        .next()
+        .ok_or_else(|| miette!("Input is empty"))?
+        .len();
+    let ydim = input.lines().count();
+
+    if ydim == 0 {
+        return Err(miette!("Input has no lines"));
+    }
+
+    if input.lines().any(|line| line.len() != xdim) {
+        return Err(miette!("Input grid is not rectangular"));
+    }
+
+    let mut map = Map {
+        grid: vec![
+            vec![
+                Node {
+                    x: 0,
+                    y: 0,
+                    value: 0
+                };
+                xdim
+            ];
+            ydim
+        ],
+        xdim,
+        ydim,
+    };
+
+    let result = parse_grid(LocatedSpan::new(input.as_bytes()))
+        .map_err(|e| miette!("Failed to parse grid: {}", e))?;
+
+    // Validate parsed values
+    for node in result.1.iter() {
+        if node.value > MAX_VALUE {
+            return Err(miette!(
+                "Invalid height value {} at line {}, column {}",
+                node.value,
+                node.position.location_line(),
+                node.position.get_column()
+            ));
+        }
+    }
+
+    result.1.iter().for_each(|node| {
+        map.add_node(Node {
+            x: node.position.get_column().saturating_sub(1),
+            y: (node.position.location_line() as usize).saturating_sub(1),
+            value: node.value,
+        });
+    });
+
+    Ok(map)
+}
+
+/// Creates a directed graph representation of the climbing map
+///
+/// Edges are created between adjacent nodes where the destination
+/// is exactly one value higher than the source.
+pub fn create_graph(map: &Map) -> Result<DiGraph<Node, ()>> {
+    create_graph_with_diagonals(map, false)
+}
+
+/// Same as [`create_graph`], but when `allow_diag` is set the four diagonal
+/// neighbors are also considered adjacent.
+pub fn create_graph_with_diagonals(map: &Map, allow_diag: bool) -> Result<DiGraph<Node, ()>> {
+    let mut graph = DiGraph::<Node, ()>::new();
+    let mut indices = HashMap::new();
+
+    // First pass: add all nodes
+    for (y, row) in map.grid.iter().enumerate() {
+        for (x, &node) in row.iter().enumerate() {
+            let idx = graph.add_node(node);
+            indices.insert((x, y), idx);
+        }
+    }
+
+    // Second pass: add edges according to rules
+    let orthogonal = [(0, 1), (1, 0), (0, -1), (-1, 0)]; // Down, Right, Up, Left
+    let diagonal = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+    let deltas: &[(i32, i32)] = if allow_diag {
+        &[orthogonal, diagonal].concat()
+    } else {
+        &orthogonal
+    };
+
+    for y in 0..map.ydim {
+        for x in 0..map.xdim {
+            let current = indices[&(x, y)];
+            let current_node = graph[current];
+
+            for &(dx, dy) in deltas {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+
+                if nx < 0 || ny < 0 || nx >= map.xdim as i32 || ny >= map.ydim as i32 {
+                    continue;
+                }
+
+                let nx = nx as usize;
+                let ny = ny as usize;
+
+                let neighbor = indices[&(nx, ny)];
+                let neighbor_node = graph[neighbor];
+
+                if neighbor_node.value == current_node.value + 1 {
+                    graph.add_edge(current, neighbor, ());
+                }
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Parses `input` and builds its trail graph in one go, handing back both so the
+/// graph can be fed to other petgraph algorithms
+pub fn build_graph(input: &str) -> Result<(Map, DiGraph<Node, ()>)> {
+    build_graph_with_diagonals(input, false)
+}
+
+/// Same as [`build_graph`], with diagonal steps allowed when `allow_diag` is set
+pub fn build_graph_with_diagonals(
+    input: &str,
+    allow_diag: bool,
+) -> Result<(Map, DiGraph<Node, ()>)> {
+    let input: &str = &grid::normalize(input);
+    let map = parse_input(input).context("Failed to parse input grid")?;
+
+    debug!("Created map with dimensions {:?}", map.dimensions());
+
+    let graph = create_graph_with_diagonals(&map, allow_diag)
+        .context("Failed to create graph representation")?;
+
+    debug!(
+        "Created graph with {} nodes and {} edges",
+        graph.node_count(),
+        graph.edge_count()
+    );
+
+    Ok((map, graph))
+}
+
+/// Per-node summary of the trails leading up from it
+#[derive(Debug, Default)]
+struct Summit {
+    /// Distinct peaks reachable from the node
+    peaks: HashSet<NodeIndex>,
+    /// Number of distinct trails from the node to any peak
+    paths: usize,
+}
+
+/// Computes the score (distinct reachable peaks) and rating (distinct trails)
+/// of every trailhead in a single pass, working down from the peaks
+///
+/// Returns a vector of tuples (trailhead_node_index, score, rating)
+pub fn analyze(graph: &DiGraph<Node, ()>) -> Result<Vec<(NodeIndex, usize, usize)>> {
+    let (trailheads, memo) = summarize(graph)?;
+
+    Ok(trailheads
+        .into_iter()
+        .map(|start| {
+            let summit = &memo[&start];
+            (start, summit.peaks.len(), summit.paths)
+        })
+        .collect())
+}
+
+/// Returns, for every trailhead, the set of peaks it can reach
+///
+/// The node indices can be mapped back to grid coordinates through the
+/// [`Node`] weights stored in the graph.
+pub fn reachable_peaks(
+    graph: &DiGraph<Node, ()>,
+) -> Result<HashMap<NodeIndex, HashSet<NodeIndex>>> {
+    let (trailheads, mut memo) = summarize(graph)?;
+
+    Ok(trailheads
+        .into_iter()
+        .filter_map(|start| memo.remove(&start).map(|summit| (start, summit.peaks)))
+        .collect())
+}
+
+fn summarize(graph: &DiGraph<Node, ()>) -> Result<(Vec<NodeIndex>, HashMap<NodeIndex, Summit>)> {
+    let peaks: HashSet<_> = graph
+        .node_indices()
+        .filter(|idx| graph[*idx].value == PEAK)
+        .collect();
+
+    let trailheads: Vec<_> = graph
+        .node_indices()
+        .filter(|idx| graph[*idx].value == TRAILHEAD)
+        .collect();
+
+    if peaks.is_empty() {
+        return Err(miette!("No peaks found in the grid"));
+    }
+
+    if trailheads.is_empty() {
+        return Err(miette!("No trailheads found in the grid"));
+    }
+
+    debug!(
+        "Found {} peaks and {} trailheads",
+        peaks.len(),
+        trailheads.len()
+    );
+
+    // Every edge climbs exactly one unit, so visiting nodes from the highest value
+    // down guarantees each neighbor is already summarized when we need it
+    let mut by_height: Vec<_> = graph.node_indices().collect();
+    by_height.sort_by_key(|idx| Reverse(graph[*idx].value));
+
+    let mut memo: HashMap<NodeIndex, Summit> = HashMap::with_capacity(by_height.len());
+
+    for current in by_height {
+        let summit = if peaks.contains(&current) {
+            Summit {
+                peaks: HashSet::from([current]),
+                paths: 1,
+            }
+        } else {
+            graph
+                .neighbors(current)
+                .fold(Summit::default(), |mut acc, neighbor| {
+                    let next = &memo[&neighbor];
+                    acc.peaks.extend(&next.peaks);
+                    acc.paths += next.paths;
+                    acc
+                })
+        };
+
+        memo.insert(current, summit);
+    }
+
+    Ok((trailheads, memo))
+}
+
+// region: parser module
+mod parser {
+    use super::*;
+
+    type Span<'a> = LocatedSpan<&'a [u8]>;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub(crate) struct LocatedNode<'a> {
+        pub value: u8,
+        pub position: Span<'a>,
+    }
+
+    pub(crate) fn parse_node(input: Span) -> IResult<Span, LocatedNode> {
+        satisfy(|c: char| c.is_ascii_digit())
+            .map(|c| LocatedNode {
+                value: (c as u8) - b'0',
+                position: input,
+            })
+            .parse(input)
+    }
+
+    pub(crate) fn parse_grid(input: Span) -> IResult<Span, Vec<LocatedNode>> {
+        let (input, lines) = separated_list1(newline, many1(parse_node))(input)?;
+        Ok((input, lines.into_iter().flatten().collect()))
+    }
+}
+
+use parser::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::visit::EdgeRef;
+
+    const INPUT: &str = "89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732";
+
+    #[test]
+    fn test_analyze() -> Result<()> {
+        let graph = create_graph(&parse_input(INPUT)?)?;
+        let trailheads = analyze(&graph)?;
+
+        assert_eq!(9, trailheads.len());
+        assert_eq!(
+            36,
+            trailheads.iter().map(|(_, score, _)| score).sum::<usize>()
+        );
+        assert_eq!(
+            81,
+            trailheads
+                .iter()
+                .map(|(_, _, rating)| rating)
+                .sum::<usize>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reachable_peaks() -> Result<()> {
+        let graph = create_graph(&parse_input(INPUT)?)?;
+        let peaks = reachable_peaks(&graph)?;
+
+        assert_eq!(9, peaks.len());
+        assert_eq!(36, peaks.values().map(HashSet::len).sum::<usize>());
+        assert!(peaks
+            .iter()
+            .all(|(start, reached)| graph[*start].value() == TRAILHEAD
+                && reached.iter().all(|peak| graph[*peak].value() == PEAK)));
+
+        // The first trailhead in the example sits at (2, 0) and reaches five peaks
+        let (_, first) = peaks
+            .iter()
+            .find(|(start, _)| graph[**start].position() == (2, 0))
+            .ok_or_else(|| miette!("Missing trailhead at (2, 0)"))?;
+        assert_eq!(5, first.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_analyze_large_map() -> Result<()> {
+        let row = "0123456789".repeat(30);
+        let input = vec![row.as_str(); 100].join("\n");
+        let graph = create_graph(&parse_input(&input)?)?;
+        let trailheads = analyze(&graph)?;
+
+        assert_eq!(3000, trailheads.len());
+        assert!(trailheads
+            .iter()
+            .all(|&(_, score, rating)| score == 1 && rating == 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph_creation() -> Result<()> {
+        let input = "12\n34";
+        let parsed = parse_input(input)?;
+        let graph = create_graph(&parsed)?;
+
+        assert_eq!(4, graph.node_count(), "Should have 4 nodes");
+        assert_eq!(2, graph.edge_count(), "Should have 2 edges");
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_graph() -> Result<()> {
+        let (map, graph) = build_graph("12\n34")?;
+
+        assert_eq!((2, 2), map.dimensions());
+        assert_eq!(4, graph.node_count());
+        // 1 -> 2 and 3 -> 4 along the rows, while 2 -> 3 only joins up diagonally
+        assert_eq!(2, graph.edge_count());
+        assert_eq!(create_graph(&map)?.edge_count(), graph.edge_count());
+
+        let (_, diagonal) = build_graph_with_diagonals("12\n34", true)?;
+        assert_eq!(3, diagonal.edge_count());
+        Ok(())
+    }
+
+    #[test]
+    fn test_edge_directions() -> Result<()> {
+        let input = "123\n654";
+        let parsed = parse_input(input)?;
+        let graph = create_graph(&parsed)?;
+
+        assert_eq!(5, graph.edge_count(), "Expected 5 edges in the graph");
+
+        let mut found_edges = [false; 5];
+
+        for edge in graph.edge_references() {
+            let from = graph[edge.source()].value;
+            let to = graph[edge.target()].value;
+
+            match (from, to) {
+                (1, 2) => found_edges[0] = true,
+                (2, 3) => found_edges[1] = true,
+                (3, 4) => found_edges[2] = true,
+                (4, 5) => found_edges[3] = true,
+                (5, 6) => found_edges[4] = true,
+                _ => panic!("Unexpected edge from {} to {}", from, to),
+            }
+        }
+
+        assert!(
+            found_edges.iter().all(|&x| x),
+            "Not all expected edges were found"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_path() -> Result<()> {
+        let input = "0123\n1234\n8765\n9876";
+        let parsed = parse_input(input)?;
+        let graph = create_graph(&parsed)?;
+        let trailheads = analyze(&graph)?;
+
+        assert_eq!(1, trailheads.len(), "Expected 1 trailhead");
+        assert!(
+            trailheads[0].1 > 0,
+            "Trailhead should reach at least one peak"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_display() -> Result<()> {
+        let input = "12\n34";
+        let map = parse_input(input)?;
+        let display = format!("{}", map);
+        assert_eq!("12\n34\n", display);
+        Ok(())
+    }
+}
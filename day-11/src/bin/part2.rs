@@ -1,4 +1,4 @@
-use day_11::part2_claude::process;
+use day_11::part2::process;
 use miette::Context;
 
 #[tracing::instrument]
@@ -78,6 +78,7 @@ struct Sequence {
 
 #[tracing::instrument]
 pub fn process(input: &str, blink_count: usize) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let input_sequence = parse_input(input)?;
     let result = process_sequence(&input_sequence, blink_count)?;
 
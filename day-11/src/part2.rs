@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use itertools::Itertools;
 use miette::{miette, IntoDiagnostic, Result};
 
@@ -87,12 +89,39 @@ struct Sequence {
 
 #[tracing::instrument]
 pub fn process(input: &str, blink_count: usize) -> Result<String> {
+    let input: &str = &grid::normalize(input);
     let sequence = parse_input(input)?;
+    Ok(count_after(&sequence, blink_count)?.to_string())
+}
+
+/// Number of stones after `count` blinks. Stones with the same value always change
+/// the same way, so only the count of each value is tracked rather than the stones.
+fn count_after(input_sequence: &Sequence, count: usize) -> Result<usize> {
+    let mut counts: HashMap<usize, usize> =
+        input_sequence.elements.iter().map(|e| e.value).counts();
+
+    for _ in 0..count {
+        let mut next = HashMap::with_capacity(counts.len());
+        for (value, stones) in counts {
+            for element in blink(&Element::new(value))? {
+                *next.entry(element.value).or_insert(0) += stones;
+            }
+        }
+        counts = next;
+    }
 
-    // Use iterative processing to avoid stack overflow
-    let final_elements = process_sequence_iterative(&sequence, blink_count)?;
+    Ok(counts.values().sum())
+}
 
-    Ok(final_elements.len().to_string())
+/// What a single stone becomes after one blink
+fn blink(element: &Element) -> Result<Vec<Element>> {
+    if element.is_zero()? {
+        Ok(vec![Element::new(1)])
+    } else if element.is_even()? {
+        element.split_digits()
+    } else {
+        Ok(vec![Element::new(element.value * 2024)])
+    }
 }
 
 fn parse_input(input: &str) -> Result<Sequence> {
@@ -107,10 +136,12 @@ fn parse_input(input: &str) -> Result<Sequence> {
 }
 
 // This version is kept for test compatibility
+#[cfg(test)]
 fn process_sequence(input_sequence: &Sequence, count: usize) -> Result<Vec<Element>> {
     process_sequence_iterative(input_sequence, count)
 }
 
+#[cfg(test)]
 fn process_sequence_iterative(input_sequence: &Sequence, count: usize) -> Result<Vec<Element>> {
     if count == 0 {
         return Ok(input_sequence.elements.clone());
@@ -124,14 +155,7 @@ fn process_sequence_iterative(input_sequence: &Sequence, count: usize) -> Result
         next.clear();
 
         for element in &current {
-            if element.is_zero()? {
-                next.push(Element::new(1));
-            } else if element.is_even()? {
-                let split_elements = element.split_digits()?;
-                next.extend(split_elements);
-            } else {
-                next.push(Element::new(element.value * 2024));
-            }
+            next.extend(blink(element)?);
         }
 
         // Ensure next buffer has enough capacity for next iteration
@@ -166,6 +190,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_count_after_matches_sequence() -> miette::Result<()> {
+        let sequence = parse_input("125 17 0 1000")?;
+        for count in 0..15 {
+            assert_eq!(
+                process_sequence(&sequence, count)?.len(),
+                count_after(&sequence, count)?
+            );
+        }
+
+        // Far past anything the full sequence could hold
+        assert_eq!("65601038650482", process("125 17", 75)?);
+        Ok(())
+    }
+
     #[test_log::test]
     fn test_single_process_sequence() -> miette::Result<()> {
         let input = "0 1 10 99 999";
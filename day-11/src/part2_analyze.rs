@@ -90,6 +90,7 @@ fn analyze_sequence(elements: &[Element]) -> Result<SequenceStats> {
 }
 
 pub fn process(input: &str, blink_count: usize) -> Result<String> {
+    let input: &str = &grid::normalize(input);
     let mut current: Vec<Element> = input
         .split_whitespace()
         .map(|x| x.parse::<usize>().into_diagnostic())
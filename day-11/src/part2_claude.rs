@@ -79,6 +79,7 @@ fn count_digits(mut n: usize) -> usize {
 }
 
 pub fn process(input: &str, blink_count: usize) -> Result<String> {
+    let input: &str = &grid::normalize(input);
     let mut stats = NumberStats::new(input)?;
 
     println!("\nInitial state:");
@@ -0,0 +1,774 @@
+use std::collections::{HashMap, HashSet};
+
+use grid::{connected_components, Direction};
+use itertools::Itertools;
+use miette::{miette, Result};
+use nom::{
+    character::complete::{newline, satisfy},
+    multi::{many1, separated_list1},
+    IResult, Parser,
+};
+use nom_locate::LocatedSpan;
+use petgraph::graph::UnGraph;
+
+pub type Position = (usize, usize);
+
+/// Fills the gaps left by short rows in a ragged map. It never parses as a plot, so
+/// padded cells are skipped during region detection.
+const PADDING: char = ' ';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Plot {
+    character: char,
+    position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Map {
+    xdim: usize,
+    ydim: usize,
+    grid: Vec<Vec<Plot>>,
+}
+
+impl Map {
+    pub fn add_plot(&mut self, plot: Plot) {
+        self.grid[plot.position.1][plot.position.0] = plot;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Region {
+    character: char,
+    cells: Vec<Position>,
+    area: usize,
+    perimeter: usize,
+    sides: usize,
+}
+
+impl Region {
+    /// Creates a new Region from a graph of connected plots with the same character.
+    /// Calculates the area (number of nodes), perimeter (exposed edges) and number of
+    /// straight fence sides of the region.
+    pub fn new(graph: UnGraph<Plot, ()>) -> Self {
+        let character = graph
+            .node_weights()
+            .next()
+            .map(|plot| plot.character)
+            .unwrap_or(' ');
+        let cells = graph.node_weights().map(|plot| plot.position).collect();
+        Self::from_cells(character, cells)
+    }
+
+    /// Creates a new Region directly from the positions of its plots
+    pub fn from_cells(character: char, cells: Vec<Position>) -> Self {
+        let cell_set = to_signed(&cells);
+        let area = cells.len();
+        let perimeter = Self::calculate_perimeter(&cell_set);
+        let sides = Self::count_corners(&cell_set);
+        let cells = cells.into_iter().sorted_by_key(|&(x, y)| (y, x)).collect();
+        Self {
+            character,
+            cells,
+            area,
+            perimeter,
+            sides,
+        }
+    }
+
+    /// calculates the total perimiter of a region
+    fn calculate_perimeter(cells: &HashSet<(i64, i64)>) -> usize {
+        cells
+            .iter()
+            .map(|&(x, y)| {
+                [(0, 1), (1, 0), (0, -1), (-1, 0)]
+                    .into_iter()
+                    .filter(|&(dx, dy)| !cells.contains(&(x + dx, y + dy)))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// calculates the number of sides of the region
+    ///
+    /// A closed fence has as many straight sides as it has corners, so this counts
+    /// corners instead. For every plot, each of its four diagonal quadrants is a
+    /// corner when either both orthogonal neighbors towards it are outside the region
+    /// (convex) or both are inside while the diagonal plot is outside (concave).
+    /// Boundaries around holes are counted the same way as the outer boundary.
+    fn count_corners(cells: &HashSet<(i64, i64)>) -> usize {
+        cells
+            .iter()
+            .map(|&(x, y)| {
+                [(1, 1), (1, -1), (-1, -1), (-1, 1)]
+                    .into_iter()
+                    .filter(|&(dx, dy)| {
+                        let horizontal = cells.contains(&(x + dx, y));
+                        let vertical = cells.contains(&(x, y + dy));
+                        let diagonal = cells.contains(&(x + dx, y + dy));
+
+                        (!horizontal && !vertical) || (horizontal && vertical && !diagonal)
+                    })
+                    .count()
+            })
+            .sum()
+    }
+
+    pub fn character(&self) -> char {
+        self.character
+    }
+
+    /// 0-based `(x, y)` positions of every plot in the region, in row-major order
+    pub fn cells(&self) -> &[Position] {
+        &self.cells
+    }
+
+    /// Smallest rectangle containing the region, as inclusive `(min, max)` corners
+    pub fn bounding_box(&self) -> (Position, Position) {
+        self.cells.iter().fold(
+            ((usize::MAX, usize::MAX), (0, 0)),
+            |((min_x, min_y), (max_x, max_y)), &(x, y)| {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+        )
+    }
+
+    /// Every exposed unit of fence, as the plot it borders and the direction facing out
+    /// of the region, in row-major order of plots. There are [`perimeter`](Self::perimeter)
+    /// of them.
+    pub fn boundary_edges(&self) -> Vec<(Position, Direction)> {
+        let cells: HashSet<Position> = self.cells.iter().copied().collect();
+
+        self.cells
+            .iter()
+            .flat_map(|&(x, y)| Direction::ALL.into_iter().map(move |dir| ((x, y), dir)))
+            .filter(|&((x, y), dir)| {
+                let (dx, dy) = dir.delta();
+                match (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                    (Some(nx), Some(ny)) => !cells.contains(&(nx, ny)),
+                    _ => true,
+                }
+            })
+            .collect()
+    }
+
+    pub fn area(&self) -> usize {
+        self.area
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.perimeter
+    }
+
+    pub fn sides(&self) -> usize {
+        self.sides
+    }
+
+    pub fn price(&self) -> usize {
+        self.area * self.perimeter
+    }
+
+    /// Price with the bulk discount applied: area times number of sides
+    pub fn bulk_price(&self) -> usize {
+        self.area * self.sides
+    }
+}
+
+fn to_signed(cells: &[Position]) -> HashSet<(i64, i64)> {
+    cells.iter().map(|&(x, y)| (x as i64, y as i64)).collect()
+}
+
+/// Labels connected regions by flood-filling the grid directly, without building
+/// an intermediate graph.
+pub fn regions(map: &Map) -> Vec<Region> {
+    regions_with_diagonals(map, false)
+}
+
+/// Same as [`regions`], but when `diag` is set plots touching only at a corner also
+/// belong to the same region
+pub fn regions_with_diagonals(map: &Map, diag: bool) -> Vec<Region> {
+    let orthogonal = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+    let diagonal = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+    let deltas: &[(isize, isize)] = if diag {
+        &[orthogonal, diagonal].concat()
+    } else {
+        &orthogonal
+    };
+
+    let mut visited = vec![vec![false; map.xdim]; map.ydim];
+    let mut regions = Vec::new();
+
+    for y in 0..map.ydim {
+        for x in 0..map.xdim {
+            if visited[y][x] || map.grid[y][x].character == PADDING {
+                continue;
+            }
+
+            let character = map.grid[y][x].character;
+            let mut cells = Vec::new();
+            let mut stack = vec![(x, y)];
+            visited[y][x] = true;
+
+            while let Some((cx, cy)) = stack.pop() {
+                cells.push(map.grid[cy][cx].position);
+
+                for &(dx, dy) in deltas {
+                    let (Some(nx), Some(ny)) =
+                        (cx.checked_add_signed(dx), cy.checked_add_signed(dy))
+                    else {
+                        continue;
+                    };
+
+                    if nx < map.xdim
+                        && ny < map.ydim
+                        && !visited[ny][nx]
+                        && map.grid[ny][nx].character == character
+                    {
+                        visited[ny][nx] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+
+            regions.push(Region::from_cells(character, cells));
+        }
+    }
+
+    regions
+}
+
+pub fn create_graph(map: &Map) -> Result<UnGraph<Plot, ()>> {
+    let mut graph = UnGraph::<Plot, ()>::new_undirected();
+    let mut indices = HashMap::new();
+
+    // create nodes for grid
+    for y in 0..map.ydim {
+        for x in 0..map.xdim {
+            let node = map.grid[y][x];
+            let idx = graph.add_node(node);
+            indices.insert((x, y), idx);
+        }
+    }
+
+    // create edges for grid
+    let deltas = [(0, 1), (1, 0)];
+
+    for y in 0..map.ydim {
+        for x in 0..map.xdim {
+            let current = indices[&(x, y)];
+
+            for (dx, dy) in deltas {
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+
+                if nx < 0 || ny < 0 || nx >= map.xdim as i32 || ny >= map.ydim as i32 {
+                    continue;
+                }
+
+                let nx = nx as usize;
+                let ny = ny as usize;
+
+                let neighbor = indices[&(nx, ny)];
+                graph.add_edge(current, neighbor, ());
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Extracts connected subgraphs where all nodes share the same character value.
+/// Returns a vector of subgraphs, each containing nodes of a single character that
+/// are connected in the original graph.
+pub fn extract_equal_value_subgraphs<E: Clone>(graph: &UnGraph<Plot, E>) -> Vec<UnGraph<Plot, E>> {
+    let plots = graph
+        .node_indices()
+        .filter(|&node| graph[node].character != PADDING);
+
+    connected_components(
+        plots,
+        |node| graph.neighbors(node),
+        |a, b| graph[a].character == graph[b].character,
+    )
+    .iter()
+    .map(|component| create_subgraph(graph, component))
+    .collect()
+}
+
+fn create_subgraph<E: Clone>(
+    graph: &UnGraph<Plot, E>,
+    component: &[petgraph::graph::NodeIndex],
+) -> UnGraph<Plot, E> {
+    let mut subgraph = UnGraph::new_undirected();
+    let mut node_map = HashMap::new();
+
+    // Add nodes
+    for &node_idx in component {
+        let new_idx = subgraph.add_node(graph[node_idx]);
+        node_map.insert(node_idx, new_idx);
+    }
+
+    // Add edges between nodes in the component
+    for &node_idx in component {
+        for neighbor in graph.neighbors(node_idx) {
+            if node_map.contains_key(&neighbor) {
+                subgraph.add_edge(
+                    node_map[&node_idx],
+                    node_map[&neighbor],
+                    graph
+                        .edge_weight(graph.find_edge(node_idx, neighbor).unwrap())
+                        .unwrap()
+                        .clone(),
+                );
+            }
+        }
+    }
+
+    subgraph
+}
+
+// region: Nom parser
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct LocatedPlot<'a> {
+    character: char,
+    position: Span<'a>,
+}
+
+fn parse_alphanumeric(input: Span) -> IResult<Span, LocatedPlot> {
+    satisfy(|c: char| c.is_ascii_alphanumeric())
+        .map(|c| LocatedPlot {
+            character: c,
+            position: input,
+        })
+        .parse(input)
+}
+
+fn parse_grid(input: Span) -> IResult<Span, Vec<LocatedPlot>> {
+    let (input, lines) = separated_list1(newline, many1(parse_alphanumeric))(input)?;
+    Ok((input, lines.into_iter().flatten().collect()))
+}
+
+/// Parses the garden map. Rows shorter than the widest one are padded on the
+/// right with [`PADDING`] plots. Anything other than trailing whitespace left after
+/// the last row is an error.
+pub fn parse_map(input: Span) -> Result<Map> {
+    let xdim = input
+        .lines()
+        .map(str::len)
+        .max()
+        .ok_or_else(|| miette!("Failed to parse lines from input"))?;
+
+    let ydim = input.lines().count();
+
+    let mut map = Map {
+        xdim,
+        ydim,
+        grid: (0..ydim)
+            .map(|y| {
+                (0..xdim)
+                    .map(|x| Plot {
+                        character: PADDING,
+                        position: (x, y),
+                    })
+                    .collect()
+            })
+            .collect(),
+    };
+
+    let (rest, plots) = parse_grid(input).map_err(|e| miette!("Failed to parse grid: {}", e))?;
+    if !rest.fragment().trim().is_empty() {
+        return Err(miette!(
+            "Unexpected input at line {}, column {}: {:?}",
+            rest.location_line(),
+            rest.get_column(),
+            rest.fragment().lines().next().unwrap_or_default()
+        ));
+    }
+
+    for plot in plots.iter() {
+        map.add_plot({
+            Plot {
+                character: plot.character,
+                // nom_locate counts from 1, plots from 0
+                position: (
+                    plot.position.get_column() - 1,
+                    plot.position.location_line() as usize - 1,
+                ),
+            }
+        });
+    }
+
+    Ok(map)
+}
+// endregion
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regions_match_graph() -> miette::Result<()> {
+        let inputs = [
+            "RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE",
+            "AAAA
+BBCD
+BBCC
+EEEC",
+            "OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO",
+        ];
+
+        for input in inputs {
+            let map = parse_map(LocatedSpan::new(input))?;
+            let flooded = regions(&map);
+            let graphed = extract_equal_value_subgraphs(&create_graph(&map)?)
+                .into_iter()
+                .map(Region::new)
+                .collect::<Vec<_>>();
+
+            assert_eq!(graphed.len(), flooded.len());
+            assert_eq!(
+                graphed.iter().map(Region::price).sum::<usize>(),
+                flooded.iter().map(Region::price).sum::<usize>()
+            );
+            assert_eq!(
+                graphed.iter().map(Region::bulk_price).sum::<usize>(),
+                flooded.iter().map(Region::bulk_price).sum::<usize>()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_boundary_edges() -> miette::Result<()> {
+        // An L of three plots in the corner: two exposed edges on the corner plot,
+        // three on each arm
+        let region = Region::from_cells('A', vec![(0, 0), (1, 0), (0, 1)]);
+        let edges = region.boundary_edges();
+
+        assert_eq!(region.perimeter(), edges.len());
+        assert_eq!(
+            vec![
+                ((0, 0), Direction::Up),
+                ((0, 0), Direction::Left),
+                ((1, 0), Direction::Up),
+                ((1, 0), Direction::Right),
+                ((1, 0), Direction::Down),
+                ((0, 1), Direction::Right),
+                ((0, 1), Direction::Down),
+                ((0, 1), Direction::Left),
+            ],
+            edges
+        );
+
+        let map = parse_map(LocatedSpan::new("AAAA\nBBCD\nBBCC\nEEEC"))?;
+        for region in regions(&map) {
+            assert_eq!(region.perimeter(), region.boundary_edges().len());
+        }
+        // The top row borders the edge of the map
+        assert_eq!(
+            ((0, 0), Direction::Up),
+            regions(&map)[0].boundary_edges()[0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_region_details() -> miette::Result<()> {
+        let input = "RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE";
+
+        let map = parse_map(LocatedSpan::new(input))?;
+        let graph = create_graph(&map)?;
+        let regions = extract_equal_value_subgraphs(&graph)
+            .into_iter()
+            .map(Region::new)
+            .collect::<Vec<_>>();
+
+        let r = regions
+            .iter()
+            .find(|region| region.character() == 'R')
+            .ok_or_else(|| miette!("No 'R' region found"))?;
+
+        assert_eq!(12, r.cells().len());
+        assert_eq!(r.area(), r.cells().len());
+        assert_eq!(((0, 0), (4, 3)), r.bounding_box());
+        assert!(r
+            .cells()
+            .iter()
+            .all(|&(x, y)| map.grid[y][x].character == 'R'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_example() -> miette::Result<()> {
+        let input = "AAAA
+BBCD
+BBCC
+EEEC";
+
+        let map = parse_map(LocatedSpan::new(input))?;
+        let graph = create_graph(&map)?;
+
+        assert_eq!(graph.node_count(), 16);
+        assert_eq!(graph.edge_count(), 24);
+
+        let subgraphs = extract_equal_value_subgraphs(&graph);
+
+        assert_eq!(subgraphs.len(), 5);
+        let valid_subgraphs = subgraphs.iter().all(|sg| {
+            sg.node_indices()
+                .next()
+                .map(|idx| "ABCDE".contains(sg[idx].character))
+                .unwrap_or(false)
+        });
+        assert!(valid_subgraphs);
+
+        let regions = subgraphs
+            .iter()
+            .map(|sg| Region::new(sg.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(regions.len(), 5);
+
+        let price = regions.iter().fold(0, |acc, region| acc + region.price());
+        assert_eq!(price, 140);
+
+        let price = regions
+            .iter()
+            .fold(0, |acc, region| acc + region.bulk_price());
+        assert_eq!(price, 80);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_sides_calc() -> miette::Result<()> {
+        let input = "AAAA
+BBCD
+BBCC
+EEEC";
+
+        let map = parse_map(LocatedSpan::new(input))?;
+        let graph = create_graph(&map)?;
+        let subgraphs = extract_equal_value_subgraphs(&graph);
+
+        let regions = subgraphs
+            .iter()
+            .map(|sg| Region::new(sg.clone()))
+            .collect::<Vec<_>>();
+
+        let sides: Vec<(char, usize)> = subgraphs
+            .iter()
+            .map(|sg| {
+                let element = sg
+                    .node_indices()
+                    .next()
+                    .map(|idx| sg[idx].character)
+                    .unwrap();
+                let sides = Region::new(sg.clone()).sides();
+                (element, sides)
+            })
+            .collect();
+
+        assert_eq!(
+            sides,
+            vec![('A', 4), ('B', 4), ('C', 8), ('D', 4), ('E', 4)]
+        );
+
+        assert_eq!(regions.len(), 5);
+
+        let price = regions
+            .iter()
+            .fold(0, |acc, region| acc + region.bulk_price());
+        assert_eq!(price, 80);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_example_2() -> miette::Result<()> {
+        let input = "OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO";
+
+        let map = parse_map(LocatedSpan::new(input))?;
+        let graph = create_graph(&map)?;
+
+        assert_eq!(graph.node_count(), 25);
+        assert_eq!(graph.edge_count(), 40);
+
+        let subgraphs = extract_equal_value_subgraphs(&graph);
+
+        assert_eq!(subgraphs.len(), 5);
+        let valid_subgraphs = subgraphs.iter().all(|sg| {
+            sg.node_indices()
+                .next()
+                .map(|idx| "OX".contains(sg[idx].character))
+                .unwrap_or(false)
+        });
+        assert!(valid_subgraphs);
+
+        let count_o = subgraphs
+            .iter()
+            .filter(|sg| {
+                sg.node_indices()
+                    .next()
+                    .map(|idx| sg[idx].character == 'O')
+                    .unwrap_or(false)
+            })
+            .count();
+
+        assert_eq!(count_o, 1);
+
+        let count_x = subgraphs
+            .iter()
+            .filter(|sg| {
+                sg.node_indices()
+                    .next()
+                    .map(|idx| sg[idx].character == 'X')
+                    .unwrap_or(false)
+            })
+            .count();
+
+        assert_eq!(count_x, 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_jagged_map() -> miette::Result<()> {
+        let input = "AAA\nAA\nAAAA\nB";
+        let map = parse_map(LocatedSpan::new(input))?;
+
+        assert_eq!((4, 4), (map.xdim, map.ydim));
+        assert_eq!(PADDING, map.grid[1][3].character);
+        assert_eq!((3, 1), map.grid[1][3].position);
+
+        let flooded = regions(&map);
+        let graphed = extract_equal_value_subgraphs(&create_graph(&map)?)
+            .into_iter()
+            .map(Region::new)
+            .collect::<Vec<_>>();
+
+        for regions in [flooded, graphed] {
+            let mut summary = regions
+                .iter()
+                .map(|region| (region.character(), region.area(), region.perimeter()))
+                .collect::<Vec<_>>();
+            summary.sort();
+            assert_eq!(vec![('A', 9, 16), ('B', 1, 4)], summary);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_map() -> miette::Result<()> {
+        let input = "AB\nCD";
+        let expected = Map {
+            xdim: 2,
+            ydim: 2,
+            grid: vec![
+                vec![
+                    Plot {
+                        character: 'A',
+                        position: (0, 0),
+                    },
+                    Plot {
+                        character: 'B',
+                        position: (1, 0),
+                    },
+                ],
+                vec![
+                    Plot {
+                        character: 'C',
+                        position: (0, 1),
+                    },
+                    Plot {
+                        character: 'D',
+                        position: (1, 1),
+                    },
+                ],
+            ],
+        };
+
+        let map = parse_map(LocatedSpan::new(input))?;
+
+        assert_eq!(map, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_map_rejects_leftover_input() -> miette::Result<()> {
+        assert!(parse_map(LocatedSpan::new("AB\r\nCD")).is_err());
+        assert!(parse_map(LocatedSpan::new("AB\nC-")).is_err());
+        assert!(parse_map(LocatedSpan::new("AB\nCD\n\n")).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_grid() -> miette::Result<()> {
+        let input = LocatedSpan::new("AB\nCD");
+
+        let expected = vec![
+            Plot {
+                character: 'A',
+                position: (1, 1),
+            },
+            Plot {
+                character: 'B',
+                position: (2, 1),
+            },
+            Plot {
+                character: 'C',
+                position: (1, 2),
+            },
+            Plot {
+                character: 'D',
+                position: (2, 2),
+            },
+        ];
+
+        let grid = parse_grid(input);
+        match grid {
+            Ok((_, parsed)) => {
+                let result: Vec<Plot> = parsed
+                    .iter()
+                    .map(|plot| Plot {
+                        character: plot.character,
+                        position: (
+                            plot.position.get_column(),
+                            plot.position.location_line() as usize,
+                        ),
+                    })
+                    .collect();
+                assert_eq!(result, expected);
+                Ok(())
+            }
+            Err(e) => Err(miette!("Error: {:?}", e)),
+        }
+    }
+}
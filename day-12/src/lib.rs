@@ -1,3 +1,5 @@
 pub mod part1;
 pub mod part1_claude;
 pub mod part2;
+
+pub mod garden;
@@ -12,6 +12,7 @@ impl Region {
 }
 
 pub fn process(input: &str) -> Result<String> {
+    let input: &str = &grid::normalize(input);
     let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
 
     let regions = find_regions(&grid);
@@ -9,17 +9,21 @@ use nom::{
 
 use miette::miette;
 
-use itertools::Itertools;
+/// Most presses of either button allowed in part 1
+pub const MAX_PRESSES: i128 = 100;
+
+/// Added to both prize coordinates in part 2
+pub const PRIZE_OFFSET: i128 = 10_000_000_000_000;
 
 #[derive(Debug, Clone, PartialEq)]
 struct SolutionPairs {
-    a: i32,
-    b: i32,
-    cost: i32,
+    a: i128,
+    b: i128,
+    cost: i128,
 }
 
 impl SolutionPairs {
-    fn new(a: i32, b: i32) -> Self {
+    fn new(a: i128, b: i128) -> Self {
         Self {
             a,
             b,
@@ -28,38 +32,205 @@ impl SolutionPairs {
     }
 }
 
+impl DataEntry {
+    /// The same machine with its prize moved `offset` further along both axes, as
+    /// part 2 does with [`PRIZE_OFFSET`].
+    pub fn with_offset(&self, offset: i128) -> Self {
+        Self {
+            button_a: self.button_a,
+            button_b: self.button_b,
+            prize: Coordinate {
+                x: self.prize.x + offset,
+                y: self.prize.y + offset,
+            },
+        }
+    }
+
+    /// Solves `a * button_a + b * button_b = prize` with Cramer's rule, keeping only
+    /// non-negative whole numbers of presses no larger than `max_presses`.
+    fn solve(&self, max_presses: Option<i128>) -> Option<SolutionPairs> {
+        let (ax, ay) = (self.button_a.dx, self.button_a.dy);
+        let (bx, by) = (self.button_b.dx, self.button_b.dy);
+        let (px, py) = (self.prize.x, self.prize.y);
+
+        let det = ax * by - ay * bx;
+        if det == 0 {
+            return Self::solve_collinear((ax, ay), (bx, by), (px, py), max_presses);
+        }
+
+        let a_num = px * by - py * bx;
+        let b_num = ax * py - ay * px;
+        if a_num % det != 0 || b_num % det != 0 {
+            return None;
+        }
+
+        let (a, b) = (a_num / det, b_num / det);
+        let limit = max_presses.unwrap_or(i128::MAX);
+        (a >= 0 && b >= 0 && a <= limit && b <= limit).then(|| SolutionPairs::new(a, b))
+    }
+
+    /// Handles parallel buttons, where there is either no solution or a whole line
+    /// of them. Picks the cheapest point on that line within the press limit.
+    fn solve_collinear(
+        (ax, ay): (i128, i128),
+        (bx, by): (i128, i128),
+        (px, py): (i128, i128),
+        max_presses: Option<i128>,
+    ) -> Option<SolutionPairs> {
+        // The prize has to lie on the line both buttons move along
+        let (dx, dy) = if (ax, ay) != (0, 0) {
+            (ax, ay)
+        } else {
+            (bx, by)
+        };
+        if dx * py - dy * px != 0 {
+            return None;
+        }
+
+        // Both equations are then multiples of each other, so solve whichever axis
+        // the buttons actually move in
+        let (u, v, w) = if ax != 0 || bx != 0 {
+            (ax, bx, px)
+        } else {
+            (ay, by, py)
+        };
+        let limit = max_presses.unwrap_or(i128::MAX);
+
+        let (a, b) = match (u, v) {
+            (0, 0) => (w == 0).then_some((0, 0))?,
+            // A button that doesn't move the claw is never worth pressing
+            (0, v) => (0, exact_div(w, v)?),
+            (u, 0) => (exact_div(w, u)?, 0),
+            (u, v) => {
+                let (g, x0, y0) = extended_gcd(u, v);
+                let scale = exact_div(w, g)?;
+                let (a0, b0) = (x0 * scale, y0 * scale);
+                let (step_a, step_b) = (v / g, u / g);
+
+                // Every solution is (a0 + k * step_a, b0 - k * step_b); keep both counts
+                // between zero and the limit
+                let k_min = ceil_div(-a0, step_a).max(ceil_div(b0.saturating_sub(limit), step_b));
+                let k_max = floor_div(b0, step_b).min(floor_div(limit.saturating_sub(a0), step_a));
+                if k_min > k_max {
+                    return None;
+                }
+
+                // Cost changes linearly in k, so one end of the range is cheapest
+                let k = if 3 * step_a - step_b > 0 {
+                    k_min
+                } else {
+                    k_max
+                };
+                (a0 + k * step_a, b0 - k * step_b)
+            }
+        };
+
+        (a <= limit && b <= limit && a * ay + b * by == py && a * ax + b * bx == px)
+            .then(|| SolutionPairs::new(a, b))
+    }
+}
+
+/// `n / d` if it divides exactly and isn't negative.
+fn exact_div(n: i128, d: i128) -> Option<i128> {
+    (n % d == 0 && n / d >= 0).then_some(n / d)
+}
+
+fn floor_div(n: i128, d: i128) -> i128 {
+    n.div_euclid(d)
+}
+
+fn ceil_div(n: i128, d: i128) -> i128 {
+    -(-n).div_euclid(d)
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    let (_, cases) =
-        parse_multiple_entries(input).map_err(|e| miette!("Failed to parse input: {}", e))?;
+    let input: &str = &grid::normalize(input);
+    Ok(total_cost(input, 0, Some(MAX_PRESSES))?.to_string())
+}
 
-    let a = 1..=100;
-    let b = 1..=100;
+/// Fewest tokens needed to win every winnable prize, after moving each prize by
+/// `offset` and optionally capping the presses per button.
+pub(crate) fn total_cost(
+    input: &str,
+    offset: i128,
+    max_presses: Option<i128>,
+) -> miette::Result<i128> {
+    Ok(solutions(input, offset, max_presses)?
+        .iter()
+        .map(|pair| pair.cost)
+        .sum())
+}
 
-    let pairs = a
-        .cartesian_product(b)
-        .map(|pair| SolutionPairs::new(pair.0, pair.1))
-        .collect::<Vec<_>>();
+/// Number of machines whose prize can be won at all under the part 1 rules.
+pub fn solvable_count(input: &str) -> miette::Result<usize> {
+    Ok(solutions(input, 0, Some(MAX_PRESSES))?.len())
+}
 
-    fn test_solution(pair: &SolutionPairs, case: &DataEntry) -> bool {
-        case.button_a.dx * pair.a + case.button_b.dx * pair.b == case.prize.x
-            && case.button_a.dy * pair.a + case.button_b.dy * pair.b == case.prize.y
-    }
+/// Tokens needed to win each machine in order, or `None` where it can't be won, after
+/// moving each prize by `offset` and optionally capping the presses per button.
+pub fn costs(
+    input: &str,
+    offset: i128,
+    max_presses: Option<i128>,
+) -> miette::Result<Vec<Option<i128>>> {
+    Ok(solve_each(input, offset, max_presses)?
+        .into_iter()
+        .map(|solution| solution.map(|pair| pair.cost))
+        .collect())
+}
 
-    let mut cost = 0;
+/// The winning presses for each machine that has them.
+fn solutions(
+    input: &str,
+    offset: i128,
+    max_presses: Option<i128>,
+) -> miette::Result<Vec<SolutionPairs>> {
+    Ok(solve_each(input, offset, max_presses)?
+        .into_iter()
+        .flatten()
+        .collect())
+}
 
-    cases.iter().for_each(|case| {
-        if let Some(case_cost) = pairs
-            .iter()
-            .filter(|pair| test_solution(pair, case))
-            .map(|pair| pair.cost)
-            .min()
-        {
-            cost += case_cost;
-        }
-    });
+/// The winning presses for every machine, in input order.
+fn solve_each(
+    input: &str,
+    offset: i128,
+    max_presses: Option<i128>,
+) -> miette::Result<Vec<Option<SolutionPairs>>> {
+    Ok(parse(input)?
+        .iter()
+        .map(|case| case.with_offset(offset).solve(max_presses))
+        .collect())
+}
 
-    Ok(cost.to_string())
+/// Every machine in the puzzle input, in order. Any number of blank lines may
+/// separate machines, and trailing whitespace is ignored.
+pub fn parse(input: &str) -> miette::Result<Vec<DataEntry>> {
+    let input: &str = &grid::normalize(input);
+    let (rest, entries) =
+        parse_multiple_entries(input).map_err(|e| miette!("Failed to parse input: {}", e))?;
+
+    if !rest.trim().is_empty() {
+        return Err(miette!(
+            "Unexpected input after the last machine: {:?}",
+            rest.lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or(rest)
+        ));
+    }
+
+    Ok(entries)
 }
 
 // region: nom parser
@@ -70,15 +241,15 @@ enum ButtonType {
 }
 
 #[derive(Debug, PartialEq)]
-struct Coordinate {
-    x: i32,
-    y: i32,
+pub struct Coordinate {
+    pub x: i128,
+    pub y: i128,
 }
 
-#[derive(Debug, PartialEq)]
-struct ButtonSlope {
-    dx: i32,
-    dy: i32,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ButtonSlope {
+    pub dx: i128,
+    pub dy: i128,
 }
 
 #[derive(Debug, PartialEq)]
@@ -87,23 +258,24 @@ struct ButtonEntry {
     coordinate: ButtonSlope,
 }
 
+/// One claw machine: how far each button moves the claw, and where the prize is
 #[derive(Debug, PartialEq)]
-struct DataEntry {
-    button_a: ButtonSlope,
-    button_b: ButtonSlope,
-    prize: Coordinate,
+pub struct DataEntry {
+    pub button_a: ButtonSlope,
+    pub button_b: ButtonSlope,
+    pub prize: Coordinate,
 }
 
-fn parse_button_number(input: &str) -> IResult<&str, i32> {
+fn parse_button_number(input: &str) -> IResult<&str, i128> {
     let (input, _) = char('+')(input)?;
     let (input, num_str) = digit1(input)?;
-    let num = num_str.parse::<i32>().unwrap();
+    let num = num_str.parse::<i128>().unwrap();
     Ok((input, num))
 }
 
-fn parse_prize_number(input: &str) -> IResult<&str, i32> {
+fn parse_prize_number(input: &str) -> IResult<&str, i128> {
     let (input, num_str) = digit1(input)?;
-    let num = num_str.parse::<i32>().unwrap();
+    let num = num_str.parse::<i128>().unwrap();
     Ok((input, num))
 }
 
@@ -204,6 +376,208 @@ Prize: X=18641, Y=10279";
         Ok(())
     }
 
+    #[test]
+    fn test_solvable_count() -> miette::Result<()> {
+        let input = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279";
+        // Only the first and third machines can be won
+        assert_eq!(2, solvable_count(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_costs() -> miette::Result<()> {
+        let input = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279";
+        let capped = costs(input, 0, Some(MAX_PRESSES))?;
+        assert_eq!(vec![Some(280), None, Some(200), None], capped);
+        assert_eq!(capped, costs(input, 0, None)?);
+
+        // Moving the prizes flips which machines can be won, but only without the cap
+        assert_eq!(
+            vec![None; 4],
+            costs(input, PRIZE_OFFSET, Some(MAX_PRESSES))?
+        );
+        let costs = costs(input, PRIZE_OFFSET, None)?;
+        assert_eq!(
+            vec![None, Some(459236326669), None, Some(416082282239)],
+            costs
+        );
+        assert_eq!(875318608908, costs.iter().flatten().sum::<i128>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_press_limit() -> miette::Result<()> {
+        // Needs 150 presses of A, so only the uncapped part 2 rules can win it
+        let input = "Button A: X+1, Y+0
+Button B: X+0, Y+1
+Prize: X=150, Y=10";
+
+        assert_eq!("0", process(input)?);
+        assert_eq!(0, solvable_count(input)?);
+        assert_eq!(460, total_cost(input, 0, None)?);
+        assert_eq!(vec![None], costs(input, 0, Some(MAX_PRESSES))?);
+        assert_eq!(vec![Some(460)], costs(input, 0, None)?);
+        assert_eq!(
+            (460 + 4 * PRIZE_OFFSET).to_string(),
+            crate::part2::process(input)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_prize() -> miette::Result<()> {
+        // Prize coordinates past 10^13 parse and solve without overflowing, and match
+        // the part 2 offset applied to the original machine
+        let small = "Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176";
+        let large = "Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=10000000012748, Y=10000000012176";
+
+        assert_eq!(459236326669, total_cost(large, 0, None)?);
+        assert_eq!(
+            total_cost(small, PRIZE_OFFSET, None)?,
+            total_cost(large, 0, None)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve() {
+        let entry = DataEntry {
+            button_a: ButtonSlope { dx: 94, dy: 34 },
+            button_b: ButtonSlope { dx: 22, dy: 67 },
+            prize: Coordinate { x: 8400, y: 5400 },
+        };
+        assert_eq!(Some(SolutionPairs::new(80, 40)), entry.solve(None));
+
+        let entry = DataEntry {
+            button_a: ButtonSlope { dx: 26, dy: 66 },
+            button_b: ButtonSlope { dx: 67, dy: 21 },
+            prize: Coordinate { x: 12748, y: 12176 },
+        };
+        assert_eq!(None, entry.solve(None));
+        assert!(entry.with_offset(PRIZE_OFFSET).solve(None).is_some());
+    }
+
+    #[test]
+    fn test_solve_collinear() {
+        let machine = |a: (i128, i128), b: (i128, i128), prize: (i128, i128)| DataEntry {
+            button_a: ButtonSlope { dx: a.0, dy: a.1 },
+            button_b: ButtonSlope { dx: b.0, dy: b.1 },
+            prize: Coordinate {
+                x: prize.0,
+                y: prize.1,
+            },
+        };
+
+        // B covers the same ground for fewer tokens
+        let entry = machine((2, 2), (1, 1), (10, 10));
+        assert_eq!(Some(SolutionPairs::new(0, 10)), entry.solve(None));
+
+        // A covers four times the distance for three times the cost
+        let entry = machine((4, 4), (1, 1), (10, 10));
+        assert_eq!(Some(SolutionPairs::new(2, 2)), entry.solve(None));
+        assert_eq!(Some(SolutionPairs::new(2, 2)), entry.solve(Some(2)));
+        assert_eq!(None, entry.solve(Some(1)));
+
+        let entry = machine((4, 4), (1, 1), (4_000, 4_000));
+        assert_eq!(Some(SolutionPairs::new(1_000, 0)), entry.solve(None));
+        assert_eq!(Some(SolutionPairs::new(999, 4)), entry.solve(Some(999)));
+
+        // Off the line, or between reachable points on it
+        assert_eq!(None, machine((2, 2), (1, 1), (10, 11)).solve(None));
+        assert_eq!(None, machine((2, 2), (4, 4), (5, 5)).solve(None));
+
+        // A button that doesn't move the claw at all
+        let entry = machine((0, 0), (3, 5), (9, 15));
+        assert_eq!(Some(SolutionPairs::new(0, 3)), entry.solve(None));
+    }
+
+    #[test]
+    fn test_parse() -> miette::Result<()> {
+        // Extra blank lines between machines and a trailing newline are fine
+        let input = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279
+";
+        let entries = parse(input)?;
+        assert_eq!(4, entries.len());
+        assert_eq!(ButtonSlope { dx: 69, dy: 23 }, entries[3].button_a);
+
+        let moved = entries
+            .iter()
+            .map(|entry| entry.with_offset(PRIZE_OFFSET).prize)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            Coordinate {
+                x: 10000000008400,
+                y: 10000000005400
+            },
+            moved[0]
+        );
+        assert_eq!(
+            Coordinate {
+                x: 10000000018641,
+                y: 10000000010279
+            },
+            moved[3]
+        );
+        // Only the prize moves
+        assert_eq!(
+            entries[3].button_a,
+            entries[3].with_offset(PRIZE_OFFSET).button_a
+        );
+
+        assert!(
+            parse("Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\noops")
+                .is_err()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_button_type() {
         assert_eq!(parse_button_type("Button A: "), Ok(("", ButtonType::A)));
@@ -1,256 +1,134 @@
-use std::fmt::Display;
-
-use nom::{
-    bytes::complete::tag,
-    character::complete::{char, digit1, newline},
-    combinator::{map, opt},
-    multi::separated_list1,
-    sequence::tuple,
-    IResult, Parser,
-};
-
-use miette::miette;
-
-#[derive(Debug, Clone, PartialEq)]
-struct Robot {
-    position: Position,
-    velocity: Velocity,
-}
+use std::path::Path;
 
-impl Robot {
-    fn new(position: Position, velocity: Velocity) -> Self {
-        Self { position, velocity }
-    }
+use crate::robots::{grid_at, largest_cluster, parse_input, period, Grid, Robot};
 
-    fn step(&mut self) {
-        self.position.0 = (self.position.0 + self.velocity.0).rem_euclid(XDIM as i32);
-        self.position.1 = (self.position.1 + self.velocity.1).rem_euclid(YDIM as i32);
-    }
-}
+const XDIM: usize = 101;
+const YDIM: usize = 103;
 
-#[derive(Debug, Clone, PartialEq)]
-struct Cell {
-    robots: Option<Vec<Robot>>,
-    position: Position,
+const TICKS: usize = 1000;
+
+#[tracing::instrument]
+pub fn process(input: &str) -> miette::Result<String> {
+    process_with_dims(input, XDIM, YDIM, TICKS)
 }
 
-impl Cell {
-    fn new(position: Position) -> Self {
-        Self {
-            robots: None,
-            position,
-        }
-    }
+/// Safety factor after `ticks` seconds in a `width` by `height` space, stepping
+/// through every frame on the way
+#[tracing::instrument(skip(input))]
+pub fn process_with_dims(
+    input: &str,
+    width: usize,
+    height: usize,
+    ticks: usize,
+) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
+    let mut robots = parse_input(input)?;
 
-    fn count(&self) -> usize {
-        match &self.robots {
-            Some(robots) => robots.len(),
-            None => 0,
-        }
-    }
+    let mut grid = Grid::new(width, height);
+    grid.place(&robots);
 
-    fn clear(&mut self) {
-        self.robots = None;
-    }
-}
+    walk_robots(&mut robots, ticks, &mut grid)?;
 
-#[derive(Debug)]
-struct GridView<'a> {
-    data: &'a [Vec<Cell>],
-    x_start: usize,
-    x_end: usize,
-    y_start: usize,
-    y_end: usize,
+    Ok(grid.safety_factor().to_string())
 }
 
-impl GridView<'_> {
-    fn count_robots(&self) -> usize {
-        self.data
-            .iter()
-            .skip(self.y_start)
-            .take(self.y_end - self.y_start)
-            .map(|row| {
-                row.iter()
-                    .skip(self.x_start)
-                    .take(self.x_end - self.x_start)
-                    .map(|cell| cell.count())
-                    .sum::<usize>()
-            })
-            .sum()
-    }
+/// First tick at which some group of at least `min_cluster` robots touch, which is
+/// the frame showing the Christmas tree
+pub fn find_tree_by_cluster(input: &str, min_cluster: usize) -> miette::Result<Option<usize>> {
+    find_tree_by_cluster_with_dims(input, XDIM, YDIM, min_cluster)
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Grid(Vec<Vec<Cell>>);
-
-impl Grid {
-    fn quadrants(&self) -> Vec<GridView> {
-        let xmid = XDIM / 2;
-        let ymid = YDIM / 2;
-
-        vec![
-            GridView {
-                data: self.0.as_slice(),
-                x_start: 0,
-                x_end: xmid,
-                y_start: 0,
-                y_end: ymid,
-            },
-            GridView {
-                data: self.0.as_slice(),
-                x_start: xmid + 1,
-                x_end: XDIM,
-                y_start: 0,
-                y_end: ymid,
-            },
-            GridView {
-                data: self.0.as_slice(),
-                x_start: 0,
-                x_end: xmid,
-                y_start: ymid + 1,
-                y_end: YDIM,
-            },
-            GridView {
-                data: self.0.as_slice(),
-                x_start: xmid + 1,
-                x_end: XDIM,
-                y_start: ymid + 1,
-                y_end: YDIM,
-            },
-        ]
-    }
-
-    fn clear(&mut self) {
-        for row in self.0.iter_mut() {
-            for cell in row.iter_mut() {
-                cell.clear();
-            }
+pub fn find_tree_by_cluster_with_dims(
+    input: &str,
+    width: usize,
+    height: usize,
+    min_cluster: usize,
+) -> miette::Result<Option<usize>> {
+    let input: &str = &grid::normalize(input);
+    let mut robots = parse_input(input)?;
+    let mut grid = Grid::new(width, height);
+
+    // Past one period the frames only repeat
+    for tick in 0..period(width, height) {
+        grid.place(&robots);
+        if largest_cluster(&grid) >= min_cluster {
+            return Ok(Some(tick));
         }
+        robots
+            .iter_mut()
+            .for_each(|robot| robot.step(width, height));
     }
-}
-
-impl Display for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.0.iter() {
-            for cell in row.iter() {
-                match &cell.robots {
-                    Some(robots) => {
-                        if robots.len() > 1 {
-                            write!(f, "X")?;
-                        } else {
-                            write!(f, "#")?;
-                        }
-                    }
-                    None => write!(f, ".")?,
-                }
-            }
-            writeln!(f)?;
-        }
 
-        Ok(())
-    }
+    Ok(None)
 }
 
-const XDIM: usize = 101;
-const YDIM: usize = 103;
-
-// const XDIM: usize = 11;
-// const YDIM: usize = 7;
-
-const TICKS: usize = 1000;
-
-#[tracing::instrument]
-pub fn process(input: &str) -> miette::Result<String> {
-    let (_, mut robots) =
-        parse_robots(input).map_err(|e| miette!("Failed to parse input: {}", e))?;
-
-    let mut grid: Grid = Grid(Vec::with_capacity(YDIM));
-
-    for y in 0..YDIM {
-        let mut row = Vec::<Cell>::with_capacity(XDIM);
-        for x in 0..XDIM {
-            row.push(Cell::new((x as i32, y as i32)));
-        }
-        grid.0.push(row);
-    }
-
-    for robot in robots.iter() {
-        let (x, y) = robot.position;
-        let cell = &mut grid.0[y as usize][x as usize];
-        match &mut cell.robots {
-            Some(robots) => robots.push(robot.clone()),
-            None => cell.robots = Some(vec![robot.clone()]),
-        }
-    }
+/// The robots as they stand `t` ticks in, drawn the same way as every other frame.
+/// Jumps straight there, which makes it cheap to eyeball a candidate tree tick.
+pub fn render_at(input: &str, t: usize) -> miette::Result<String> {
+    render_at_with_dims(input, t, XDIM, YDIM)
+}
 
-    walk_robots(&mut robots, TICKS, &mut grid)?;
+pub fn render_at_with_dims(
+    input: &str,
+    t: usize,
+    width: usize,
+    height: usize,
+) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
+    let robots = parse_input(input)?;
 
-    let quadrants = grid.quadrants();
+    Ok(grid_at(&robots, t, width, height).to_string())
+}
 
-    let robot_count: usize = quadrants
-        .iter()
-        .fold(1, |acc, quadrant| acc * quadrant.count_robots());
+/// Writes the [`render_at`] frame for tick `t` to `path`, to keep a candidate tree
+/// from [`find_tree_by_cluster`] around for a closer look
+pub fn dump_frame(input: &str, t: usize, path: impl AsRef<Path>) -> miette::Result<()> {
+    dump_frame_with_dims(input, t, path, XDIM, YDIM)
+}
 
-    Ok(robot_count.to_string())
+pub fn dump_frame_with_dims(
+    input: &str,
+    t: usize,
+    path: impl AsRef<Path>,
+    width: usize,
+    height: usize,
+) -> miette::Result<()> {
+    let path = path.as_ref();
+    let frame = render_at_with_dims(input, t, width, height)?;
+    std::fs::write(path, frame)
+        .map_err(|e| miette::miette!("Failed to write {}: {}", path.display(), e))
 }
 
 fn walk_robots(robots: &mut [Robot], ticks: usize, grid: &mut Grid) -> miette::Result<()> {
+    walk_robots_with(robots, ticks, grid, |_, _| {})
+}
+
+/// Steps the robots one tick at a time, handing each frame to `observe` so the
+/// caller can print, snapshot, or search it
+fn walk_robots_with<F>(
+    robots: &mut [Robot],
+    ticks: usize,
+    grid: &mut Grid,
+    mut observe: F,
+) -> miette::Result<()>
+where
+    F: FnMut(usize, &Grid),
+{
+    let (width, height) = (grid.width(), grid.height());
+
     (1..=ticks).for_each(|tick| {
         robots.iter_mut().for_each(|robot| {
-            robot.step();
+            robot.step(width, height);
         });
 
-        println!("time: {tick}\n{grid}");
+        grid.place(robots);
 
-        grid.clear();
-        for robot in robots.iter() {
-            let (x, y) = robot.position;
-            let cell = &mut grid.0[y as usize][x as usize];
-            match &mut cell.robots {
-                Some(robots) => robots.push(robot.clone()),
-                None => cell.robots = Some(vec![robot.clone()]),
-            }
-        }
+        observe(tick, grid);
     });
 
     Ok(())
 }
 
-// region: nom parser
-type Position = (i32, i32);
-type Velocity = (i32, i32);
-
-fn parse_signed_digit(input: &str) -> IResult<&str, i32> {
-    let (input, sign) = map(opt(char('-')), |minus| match minus {
-        Some(_) => -1,
-        None => 1,
-    })(input)?;
-    let (input, digit) = digit1(input)?;
-
-    Ok((input, sign * digit.parse::<i32>().unwrap()))
-}
-
-fn parse_numbers(input: &str) -> IResult<&str, (i32, i32)> {
-    let (input, (x, _, y)) = tuple((parse_signed_digit, tag(","), parse_signed_digit))(input)?;
-
-    Ok((input, (x, y)))
-}
-
-fn parse_line(input: &str) -> IResult<&str, (Position, Velocity)> {
-    let (input, (_, position, _, velocity)) =
-        tuple((tag("p="), parse_numbers, tag(" v="), parse_numbers))(input)?;
-
-    Ok((input, (position, velocity)))
-}
-
-fn parse_robots(input: &str) -> IResult<&str, Vec<Robot>> {
-    let (input, output) =
-        separated_list1(newline, parse_line.map(|(p, v)| Robot::new(p, v)))(input)?;
-
-    Ok((input, output))
-}
-// endregion
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -270,12 +148,13 @@ p=9,3 v=2,3
 p=7,3 v=-1,2
 p=2,4 v=2,-3
 p=9,5 v=-3,-3";
-        assert_eq!("12", process(input)?);
+        assert_eq!("12", process_with_dims(input, 11, 7, 100)?);
+        assert_eq!("60", process_with_dims(input, XDIM, YDIM, TICKS)?);
         Ok(())
     }
 
     #[test]
-    fn test_parse_robots() -> miette::Result<()> {
+    fn test_walk_robots_with() -> miette::Result<()> {
         let input = "\
 p=0,4 v=3,-3
 p=6,3 v=-1,-3
@@ -289,40 +168,83 @@ p=9,3 v=2,3
 p=7,3 v=-1,2
 p=2,4 v=2,-3
 p=9,5 v=-3,-3";
+        let mut robots = parse_input(input)?;
+        let mut grid = Grid::new(11, 7);
+        grid.place(&robots);
+
+        let mut frames = vec![];
+        walk_robots_with(&mut robots, 100, &mut grid, |tick, frame| {
+            frames.push((tick, frame.safety_factor()));
+        })?;
+
+        assert_eq!(100, frames.len());
+        assert_eq!(
+            (1..=100).collect::<Vec<_>>(),
+            frames.iter().map(|(tick, _)| *tick).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&(100, 12)), frames.last());
+        Ok(())
+    }
 
-        let (_, mut robots) =
-            parse_robots(input).map_err(|e| miette!("Failed to parse input: {}", e))?;
-
-        dbg!(format!(
-            "t0: ({},{})",
-            robots[10].position.0, robots[10].position.1
-        ));
-        robots[10].step();
-        dbg!(format!(
-            "1s: ({},{})",
-            robots[10].position.0, robots[10].position.1
-        ));
-        robots[10].step();
-        dbg!(format!(
-            "2s: ({},{})",
-            robots[10].position.0, robots[10].position.1
-        ));
-        robots[10].step();
-        dbg!(format!(
-            "3s: ({},{})",
-            robots[10].position.0, robots[10].position.1
-        ));
-        robots[10].step();
-        dbg!(format!(
-            "4s: ({},{})",
-            robots[10].position.0, robots[10].position.1
-        ));
-        robots[10].step();
-        dbg!(format!(
-            "5s: ({},{})",
-            robots[10].position.0, robots[10].position.1
-        ));
+    #[test]
+    fn test_find_tree_by_cluster() -> miette::Result<()> {
+        // These four robots meet in a 2x2 block at tick 3, and two of them touch at tick 1
+        let input = "\
+p=2,3 v=1,0
+p=0,3 v=2,0
+p=8,1 v=-1,1
+p=8,3 v=3,-2";
+
+        assert_eq!(Some(3), find_tree_by_cluster_with_dims(input, 11, 7, 4)?);
+        assert_eq!(Some(1), find_tree_by_cluster_with_dims(input, 11, 7, 2)?);
+        assert_eq!(None, find_tree_by_cluster_with_dims(input, 11, 7, 5)?);
+        Ok(())
+    }
 
+    #[test]
+    fn test_render_at() -> miette::Result<()> {
+        let input = "\
+p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2
+p=2,0 v=2,-1
+p=0,0 v=1,3
+p=3,0 v=-2,-2
+p=7,6 v=-1,-3
+p=3,0 v=-1,-2
+p=9,3 v=2,3
+p=7,3 v=-1,2
+p=2,4 v=2,-3
+p=9,5 v=-3,-3";
+        let mut robots = parse_input(input)?;
+        let mut grid = Grid::new(11, 7);
+        grid.place(&robots);
+        assert_eq!(grid.to_string(), render_at_with_dims(input, 0, 11, 7)?);
+
+        // Jumping ahead draws the same frame as stepping there
+        walk_robots(&mut robots, 100, &mut grid)?;
+        assert_eq!(grid.to_string(), render_at_with_dims(input, 100, 11, 7)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_frame() -> miette::Result<()> {
+        let input = "\
+p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2";
+
+        let path = std::env::temp_dir().join("day-14-dump_frame.txt");
+        dump_frame_with_dims(input, 7, &path, 11, 7)?;
+        let dumped = std::fs::read_to_string(&path).map_err(|e| miette::miette!(e))?;
+        std::fs::remove_file(&path).map_err(|e| miette::miette!(e))?;
+        assert_eq!(render_at_with_dims(input, 7, 11, 7)?, dumped);
+
+        // A directory that isn't there can't be written to
+        let missing = std::env::temp_dir()
+            .join("day-14-missing")
+            .join("frame.txt");
+        assert!(dump_frame_with_dims(input, 7, missing, 11, 7).is_err());
         Ok(())
     }
 }
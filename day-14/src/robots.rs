@@ -0,0 +1,457 @@
+use std::{collections::HashSet, fmt::Display};
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, digit1, newline},
+    combinator::{map, opt},
+    multi::separated_list1,
+    sequence::tuple,
+    IResult, Parser,
+};
+
+use miette::miette;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Robot {
+    pub(crate) position: Position,
+    velocity: Velocity,
+}
+
+impl Robot {
+    fn new(position: Position, velocity: Velocity) -> Self {
+        Self { position, velocity }
+    }
+
+    /// Moves one tick, wrapping around a `width` by `height` space
+    pub(crate) fn step(&mut self, width: usize, height: usize) {
+        self.position.0 = (self.position.0 + self.velocity.0).rem_euclid(width as i32);
+        self.position.1 = (self.position.1 + self.velocity.1).rem_euclid(height as i32);
+    }
+
+    /// Where the robot will be after `t` ticks, without stepping through them
+    pub(crate) fn position_at(&self, t: i64, width: usize, height: usize) -> Position {
+        let x =
+            (i64::from(self.position.0) + i64::from(self.velocity.0) * t).rem_euclid(width as i64);
+        let y =
+            (i64::from(self.position.1) + i64::from(self.velocity.1) * t).rem_euclid(height as i64);
+        (x as i32, y as i32)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    robots: Option<Vec<Robot>>,
+    position: Position,
+}
+
+impl Cell {
+    fn new(position: Position) -> Self {
+        Self {
+            robots: None,
+            position,
+        }
+    }
+
+    fn count(&self) -> usize {
+        match &self.robots {
+            Some(robots) => robots.len(),
+            None => 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.robots = None;
+    }
+}
+
+#[derive(Debug)]
+struct GridView<'a> {
+    data: &'a [Vec<Cell>],
+    x_start: usize,
+    x_end: usize,
+    y_start: usize,
+    y_end: usize,
+}
+
+impl GridView<'_> {
+    fn count_robots(&self) -> usize {
+        self.data
+            .iter()
+            .skip(self.y_start)
+            .take(self.y_end - self.y_start)
+            .map(|row| {
+                row.iter()
+                    .skip(self.x_start)
+                    .take(self.x_end - self.x_start)
+                    .map(|cell| cell.count())
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Grid(Vec<Vec<Cell>>);
+
+impl Grid {
+    /// An empty `width` by `height` grid
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        Self(
+            (0..height)
+                .map(|y| {
+                    (0..width)
+                        .map(|x| Cell::new((x as i32, y as i32)))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        self.0.first().map_or(0, |row| row.len())
+    }
+
+    pub(crate) fn height(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Replaces whatever the grid held with `robots`
+    pub(crate) fn place(&mut self, robots: &[Robot]) {
+        self.clear();
+        for robot in robots {
+            let (x, y) = robot.position;
+            let cell = &mut self.0[y as usize][x as usize];
+            match &mut cell.robots {
+                Some(robots) => robots.push(robot.clone()),
+                None => cell.robots = Some(vec![robot.clone()]),
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        for row in self.0.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.clear();
+            }
+        }
+    }
+
+    /// The four quadrants, leaving out the middle row and column
+    fn quadrants(&self) -> Vec<GridView<'_>> {
+        let (width, height) = (self.width(), self.height());
+        let xmid = width / 2;
+        let ymid = height / 2;
+
+        vec![
+            GridView {
+                data: self.0.as_slice(),
+                x_start: 0,
+                x_end: xmid,
+                y_start: 0,
+                y_end: ymid,
+            },
+            GridView {
+                data: self.0.as_slice(),
+                x_start: xmid + 1,
+                x_end: width,
+                y_start: 0,
+                y_end: ymid,
+            },
+            GridView {
+                data: self.0.as_slice(),
+                x_start: 0,
+                x_end: xmid,
+                y_start: ymid + 1,
+                y_end: height,
+            },
+            GridView {
+                data: self.0.as_slice(),
+                x_start: xmid + 1,
+                x_end: width,
+                y_start: ymid + 1,
+                y_end: height,
+            },
+        ]
+    }
+
+    /// Product of the robot counts in each quadrant
+    pub(crate) fn safety_factor(&self) -> usize {
+        self.quadrants()
+            .iter()
+            .fold(1, |acc, quadrant| acc * quadrant.count_robots())
+    }
+}
+
+impl Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.0.iter() {
+            for cell in row.iter() {
+                match &cell.robots {
+                    Some(robots) => {
+                        if robots.len() > 1 {
+                            write!(f, "X")?;
+                        } else {
+                            write!(f, "#")?;
+                        }
+                    }
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Ticks after which every robot is back where it started, `lcm(width, height)`
+pub(crate) fn period(width: usize, height: usize) -> usize {
+    width / gcd(width, height) * height
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Size of the biggest group of occupied cells joined up, down, left or right.
+/// The Christmas tree frame stands out with one unusually large group.
+pub(crate) fn largest_cluster(grid: &Grid) -> usize {
+    let occupied = grid
+        .0
+        .iter()
+        .flatten()
+        .filter(|cell| cell.count() > 0)
+        .map(|cell| cell.position)
+        .collect::<HashSet<_>>();
+
+    let mut seen = HashSet::new();
+    let mut largest = 0;
+
+    for &start in &occupied {
+        if !seen.insert(start) {
+            continue;
+        }
+
+        let mut stack = vec![start];
+        let mut size = 0;
+        while let Some((x, y)) = stack.pop() {
+            size += 1;
+            for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if occupied.contains(&next) && seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        largest = largest.max(size);
+    }
+
+    largest
+}
+
+/// Safety factor `t` ticks after `robots`, jumping straight to that frame. Robots on
+/// the middle row or column count towards no quadrant, as in [`Grid::safety_factor`].
+pub(crate) fn safety_factor_at(robots: &[Robot], t: usize, width: usize, height: usize) -> usize {
+    grid_at(robots, t, width, height).safety_factor()
+}
+
+/// A `width` by `height` grid holding `robots` as they stand `t` ticks later
+pub(crate) fn grid_at(robots: &[Robot], t: usize, width: usize, height: usize) -> Grid {
+    let moved = robots
+        .iter()
+        .map(|robot| Robot::new(robot.position_at(t as i64, width, height), robot.velocity))
+        .collect::<Vec<_>>();
+
+    let mut grid = Grid::new(width, height);
+    grid.place(&moved);
+    grid
+}
+
+pub(crate) fn parse_input(input: &str) -> miette::Result<Vec<Robot>> {
+    let (_, robots) = parse_robots(input).map_err(|e| miette!("Failed to parse input: {}", e))?;
+    Ok(robots)
+}
+
+// region: nom parser
+type Position = (i32, i32);
+type Velocity = (i32, i32);
+
+fn parse_signed_digit(input: &str) -> IResult<&str, i32> {
+    let (input, sign) = map(opt(char('-')), |minus| match minus {
+        Some(_) => -1,
+        None => 1,
+    })(input)?;
+    let (input, digit) = digit1(input)?;
+
+    Ok((input, sign * digit.parse::<i32>().unwrap()))
+}
+
+fn parse_numbers(input: &str) -> IResult<&str, (i32, i32)> {
+    let (input, (x, _, y)) = tuple((parse_signed_digit, tag(","), parse_signed_digit))(input)?;
+
+    Ok((input, (x, y)))
+}
+
+fn parse_line(input: &str) -> IResult<&str, (Position, Velocity)> {
+    let (input, (_, position, _, velocity)) =
+        tuple((tag("p="), parse_numbers, tag(" v="), parse_numbers))(input)?;
+
+    Ok((input, (position, velocity)))
+}
+
+fn parse_robots(input: &str) -> IResult<&str, Vec<Robot>> {
+    let (input, output) =
+        separated_list1(newline, parse_line.map(|(p, v)| Robot::new(p, v)))(input)?;
+
+    Ok((input, output))
+}
+// endregion
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_robots() -> miette::Result<()> {
+        let input = "\
+p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2
+p=2,0 v=2,-1
+p=0,0 v=1,3
+p=3,0 v=-2,-2
+p=7,6 v=-1,-3
+p=3,0 v=-1,-2
+p=9,3 v=2,3
+p=7,3 v=-1,2
+p=2,4 v=2,-3
+p=9,5 v=-3,-3";
+
+        let mut robots = parse_input(input)?;
+        assert_eq!(12, robots.len());
+
+        // The puzzle's worked example for the robot at p=2,4 v=2,-3
+        let robot = &mut robots[10];
+        let mut positions = vec![robot.position];
+        for _ in 0..5 {
+            robot.step(11, 7);
+            positions.push(robot.position);
+        }
+        assert_eq!(
+            vec![(2, 4), (4, 1), (6, 5), (8, 2), (10, 6), (1, 3)],
+            positions
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_at() -> miette::Result<()> {
+        let robots = parse_input("p=2,4 v=2,-3\np=9,5 v=-3,-3")?;
+
+        for robot in robots {
+            let mut stepped = robot.clone();
+            for _ in 0..100 {
+                stepped.step(101, 103);
+            }
+            assert_eq!(stepped.position, robot.position_at(100, 101, 103));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_period() -> miette::Result<()> {
+        assert_eq!(10403, period(101, 103));
+        assert_eq!(77, period(11, 7));
+        assert_eq!(12, period(4, 6));
+
+        let robot = &parse_input("p=2,4 v=2,-3")?[0];
+        for t in [0, 1, 100, 5000] {
+            assert_eq!(
+                robot.position_at(t, 101, 103),
+                robot.position_at(t + period(101, 103) as i64, 101, 103)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_largest_cluster() {
+        let mut grid = Grid::new(11, 7);
+        assert_eq!(0, largest_cluster(&grid));
+
+        // A 3x3 block, an L of four touching it only diagonally, and a lone robot
+        let positions = [
+            (1, 1),
+            (2, 1),
+            (3, 1),
+            (1, 2),
+            (2, 2),
+            (3, 2),
+            (1, 3),
+            (2, 3),
+            (3, 3),
+            (4, 4),
+            (5, 4),
+            (5, 5),
+            (5, 6),
+            (9, 0),
+        ];
+        let robots = positions
+            .into_iter()
+            .map(|position| Robot::new(position, (0, 0)))
+            .collect::<Vec<_>>();
+        grid.place(&robots);
+
+        assert_eq!(9, largest_cluster(&grid));
+    }
+
+    #[test]
+    fn test_quadrants() {
+        let mut grid = Grid::new(11, 7);
+        assert_eq!((11, 7), (grid.width(), grid.height()));
+
+        let robots = [(0, 0), (10, 0), (0, 6), (10, 6), (5, 3)]
+            .into_iter()
+            .map(|position| Robot::new(position, (0, 0)))
+            .collect::<Vec<_>>();
+        grid.place(&robots);
+
+        let counts = grid
+            .quadrants()
+            .iter()
+            .map(|quadrant| quadrant.count_robots())
+            .collect::<Vec<_>>();
+        // The robot in the middle belongs to no quadrant
+        assert_eq!(vec![1, 1, 1, 1], counts);
+        assert_eq!(1, grid.safety_factor());
+    }
+
+    #[test]
+    fn test_safety_factor_at() -> miette::Result<()> {
+        let input = "\
+p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2
+p=2,0 v=2,-1
+p=0,0 v=1,3
+p=3,0 v=-2,-2
+p=7,6 v=-1,-3
+p=3,0 v=-1,-2
+p=9,3 v=2,3
+p=7,3 v=-1,2
+p=2,4 v=2,-3
+p=9,5 v=-3,-3";
+        let robots = parse_input(input)?;
+
+        assert_eq!(12, safety_factor_at(&robots, 100, 11, 7));
+        // A whole period later the frame, and so the factor, is the same
+        assert_eq!(
+            safety_factor_at(&robots, 100, 11, 7),
+            safety_factor_at(&robots, 100 + period(11, 7), 11, 7)
+        );
+        Ok(())
+    }
+}
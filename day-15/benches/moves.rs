@@ -0,0 +1,65 @@
+//! Replays the large warehouse example's moves over and over, once turning the grid
+//! so every move is rightwards and once pushing in place. Throughput is moves/second.
+use day_15::part1::{find_robot, parse_input, Direction, Grid, Robot};
+use divan::{counter::ItemsCount, Bencher};
+
+fn main() {
+    divan::main();
+}
+
+const EXAMPLE_LARGE: &str = "\
+##########
+#..O..O.O#
+#......O.#
+#.OO..O.O#
+#..O@..O.#
+#O#..O...#
+#O..O..O.#
+#.OO.O.OO#
+#....O...#
+##########
+
+<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
+
+/// Number of times the full move list is replayed per iteration
+const REPLAYS: [usize; 3] = [1, 10, 100];
+
+fn replay(
+    bencher: Bencher,
+    replays: usize,
+    step: fn(&mut Robot, &mut Grid, Direction) -> miette::Result<()>,
+) {
+    let (grid, path) = parse_input(EXAMPLE_LARGE).unwrap();
+    let (x, y) = find_robot(&grid).unwrap();
+
+    bencher
+        .counter(ItemsCount::new(path.0.len() * replays))
+        .with_inputs(|| (grid.clone(), Robot::new(x, y)))
+        .bench_local_values(|(mut grid, mut robot)| {
+            for _ in 0..replays {
+                for &direction in &path.0 {
+                    step(&mut robot, &mut grid, direction).unwrap();
+                }
+            }
+            divan::black_box(grid)
+        });
+}
+
+#[divan::bench(args = REPLAYS)]
+fn execute_move(bencher: Bencher, replays: usize) {
+    replay(bencher, replays, Robot::execute_move);
+}
+
+#[divan::bench(args = REPLAYS)]
+fn push(bencher: Bencher, replays: usize) {
+    replay(bencher, replays, Robot::push);
+}
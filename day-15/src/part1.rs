@@ -1,20 +1,16 @@
-use crate::part1::robot::*;
+pub use self::{
+    error::GameError,
+    grid::{find_robot, Grid},
+    parser::parse_input,
+    robot::{Direction, Path, Robot},
+};
 
 #[tracing::instrument]
-pub fn process(_input: &str) -> miette::Result<String> {
-    let (mut grid, path) = parser::parse_input(_input)?;
-
-    let (robot_x, robot_y) = grid
-        .cells
-        .iter()
-        .enumerate()
-        .find_map(|(y, row)| {
-            row.iter()
-                .enumerate()
-                .find(|(_, cell)| cell.is_robot())
-                .map(|(x, _)| (x as i32, y as i32))
-        })
-        .expect("Robot not found in grid");
+pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &::grid::normalize(input);
+    let (mut grid, path) = parser::parse_input(input)?;
+
+    let (robot_x, robot_y) = grid::find_robot(&grid)?;
 
     let mut robot = Robot::new(robot_x, robot_y);
 
@@ -31,7 +27,7 @@ mod error {
 
     #[derive(Debug, Error, Diagnostic)]
     #[diagnostic(code(game_error))]
-    pub(crate) enum GameError {
+    pub enum GameError {
         #[error("Failed to parse grid: {0}")]
         Parse(String),
 
@@ -60,7 +56,7 @@ mod error {
         code(parse_error),
         help("Input must contain only dots (.), digits (0-9), or uppercase letters (A-Z)")
     )]
-    pub(crate) struct GridParseError {
+    pub struct GridParseError {
         #[source_code]
         pub src: String,
         #[label("Parse error occurred here")]
@@ -70,10 +66,14 @@ mod error {
 }
 
 mod grid {
-    use crate::part1::parser::{BOX, EMPTY, ROBOT, WALL};
+    use crate::part1::{
+        error::GameError,
+        parser::{BOX, EMPTY, ROBOT, WALL},
+    };
     use std::fmt::{self, Display, Formatter};
 
     #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub(crate) struct GridCell {
         pub(crate) x: i32,
         pub(crate) y: i32,
@@ -102,13 +102,69 @@ mod grid {
         }
     }
 
+    /// With the `serde` feature a grid snapshots as one string per row, just as it
+    /// prints, rather than a struct per cell
     #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-    pub(crate) struct Grid {
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde::Serialize, serde::Deserialize),
+        serde(into = "Vec<String>", try_from = "Vec<String>")
+    )]
+    pub struct Grid {
         pub(crate) cells: Vec<Vec<GridCell>>,
         pub(crate) width: i32,
         pub(crate) height: i32,
     }
 
+    impl From<Grid> for Vec<String> {
+        fn from(grid: Grid) -> Self {
+            grid.cells
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.cell).collect())
+                .collect()
+        }
+    }
+
+    /// Rebuilds a grid from its rows, numbering cells by column and row
+    impl TryFrom<Vec<String>> for Grid {
+        type Error = GameError;
+
+        fn try_from(rows: Vec<String>) -> Result<Self, Self::Error> {
+            let width = rows.first().map_or(0, |row| row.chars().count());
+
+            let cells = rows
+                .iter()
+                .enumerate()
+                .map(|(y, row)| {
+                    if row.chars().count() != width {
+                        return Err(GameError::Parse(format!(
+                            "row {} is not {} cells wide",
+                            y, width
+                        )));
+                    }
+                    row.chars()
+                        .enumerate()
+                        .map(|(x, cell)| match cell {
+                            ROBOT | WALL | BOX | EMPTY => {
+                                Ok(GridCell::new(x as i32, y as i32, cell))
+                            }
+                            _ => Err(GameError::Parse(format!(
+                                "invalid cell '{}' at ({}, {})",
+                                cell, x, y
+                            ))),
+                        })
+                        .collect()
+                })
+                .collect::<Result<Vec<Vec<GridCell>>, _>>()?;
+
+            Ok(Grid {
+                cells,
+                width: width as i32,
+                height: rows.len() as i32,
+            })
+        }
+    }
+
     impl Display for Grid {
         fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
             for row in &self.cells {
@@ -145,28 +201,21 @@ mod grid {
         }
 
         pub(crate) fn transpose(&mut self) -> miette::Result<()> {
-            let height = self.height as usize;
-            let width = self.width as usize;
-
-            let mut transposed = vec![vec![]; width];
-
-            for (j, row) in transposed.iter_mut().enumerate().take(width) {
-                for (i, cell) in self.cells.iter().enumerate().take(height) {
-                    let mut new_cell = cell[j].clone();
-                    new_cell.x = i as i32;
-                    new_cell.y = j as i32;
-                    row.push(new_cell);
+            self.cells = grid::matrix::transpose(&self.cells);
+            for (y, row) in self.cells.iter_mut().enumerate() {
+                for (x, cell) in row.iter_mut().enumerate() {
+                    cell.x = x as i32;
+                    cell.y = y as i32;
                 }
             }
 
-            self.cells = transposed;
             std::mem::swap(&mut self.width, &mut self.height);
             Ok(())
         }
 
         pub(crate) fn reverse_rows(&mut self) -> miette::Result<()> {
+            self.cells = grid::matrix::reverse_rows(&self.cells);
             for row in self.cells.iter_mut() {
-                row.reverse();
                 let width = row.len();
                 for (i, cell) in row.iter_mut().enumerate() {
                     cell.x = (width - 1 - i) as i32;
@@ -175,6 +224,33 @@ mod grid {
             Ok(())
         }
 
+        /// Every `(x, y)` where the two grids hold different cells, with this grid's
+        /// cell first. Where one grid is larger the other counts as holding `' '`.
+        pub fn diff(&self, other: &Grid) -> Vec<(i32, i32, char, char)> {
+            let cell_at = |grid: &Grid, x: usize, y: usize| {
+                grid.cells
+                    .get(y)
+                    .and_then(|row| row.get(x))
+                    .map_or(' ', |cell| cell.cell)
+            };
+            let height = self.cells.len().max(other.cells.len());
+            let width = self
+                .cells
+                .iter()
+                .chain(&other.cells)
+                .map(Vec::len)
+                .max()
+                .unwrap_or(0);
+
+            (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .filter_map(|(x, y)| {
+                    let (ours, theirs) = (cell_at(self, x, y), cell_at(other, x, y));
+                    (ours != theirs).then_some((x as i32, y as i32, ours, theirs))
+                })
+                .collect()
+        }
+
         pub(crate) fn get_grid_gps(&self) -> i32 {
             self.cells
                 .iter()
@@ -192,13 +268,40 @@ mod grid {
                 .sum()
         }
     }
+
+    /// Position of the one robot on the grid. No robot, or several as in a badly
+    /// doubled grid, is an error rather than a guess.
+    pub fn find_robot(grid: &Grid) -> miette::Result<(i32, i32)> {
+        let robots = grid
+            .cells
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.is_robot())
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect::<Vec<_>>();
+
+        match robots[..] {
+            [robot] => Ok(robot),
+            [] => Err(GameError::Parse(format!("No robot ({ROBOT}) found in the grid")).into()),
+            _ => Err(GameError::Parse(format!(
+                "Expected one robot ({ROBOT}) but found {} at {:?}",
+                robots.len(),
+                robots
+            ))
+            .into()),
+        }
+    }
 }
 
 mod robot {
     use crate::part1::{
         error::GameError,
         grid::{Grid, GridCell},
-        parser::{EMPTY, ROBOT},
+        parser::{BOX, EMPTY, ROBOT},
     };
 
     #[derive(Debug, Clone, Copy)]
@@ -209,22 +312,34 @@ mod robot {
         Right,
     }
 
+    impl Direction {
+        /// Step as `(dx, dy)`, with `y` growing downwards
+        pub fn delta(self) -> (i32, i32) {
+            match self {
+                Direction::Up => (0, -1),
+                Direction::Down => (0, 1),
+                Direction::Left => (-1, 0),
+                Direction::Right => (1, 0),
+            }
+        }
+    }
+
     #[derive(Debug, Clone)]
-    pub(crate) struct Path(pub(crate) Vec<Direction>);
+    pub struct Path(pub Vec<Direction>);
 
     #[derive(Debug, Clone)]
-    pub(crate) struct Robot {
+    pub struct Robot {
         pub(crate) current: GridCell,
     }
 
     impl Robot {
-        pub(crate) fn new(x: i32, y: i32) -> Self {
+        pub fn new(x: i32, y: i32) -> Self {
             Self {
                 current: GridCell::new(x, y, ROBOT),
             }
         }
 
-        pub(crate) fn execute_move(
+        pub fn execute_move(
             &mut self,
             grid: &mut Grid,
             direction: Direction,
@@ -289,6 +404,36 @@ mod robot {
             }
         }
 
+        /// Same move as [`execute_move`](Self::execute_move), but walks the grid in
+        /// `direction` directly instead of turning it so the move is always rightwards
+        pub fn push(&mut self, grid: &mut Grid, direction: Direction) -> miette::Result<()> {
+            let (dx, dy) = direction.delta();
+            let (x, y) = (self.current.x, self.current.y);
+            let in_bounds = |x: i32, y: i32| x >= 0 && y >= 0 && x < grid.width && y < grid.height;
+
+            // Skip over the line of boxes, if any, to the first cell past it
+            let (mut end_x, mut end_y) = (x + dx, y + dy);
+            while in_bounds(end_x, end_y) && grid.cells[end_y as usize][end_x as usize].is_box() {
+                end_x += dx;
+                end_y += dy;
+            }
+
+            if !in_bounds(end_x, end_y) || !grid.cells[end_y as usize][end_x as usize].is_empty() {
+                return Ok(());
+            }
+
+            // Shifting a line of boxes one step only changes its two ends
+            if (end_x, end_y) != (x + dx, y + dy) {
+                grid.cells[end_y as usize][end_x as usize].cell = BOX;
+            }
+            grid.cells[(y + dy) as usize][(x + dx) as usize].cell = ROBOT;
+            grid.cells[y as usize][x as usize].cell = EMPTY;
+            self.current.x += dx;
+            self.current.y += dy;
+
+            Ok(())
+        }
+
         pub(crate) fn execute_movement(&mut self, grid: &mut Grid) -> miette::Result<()> {
             let row = grid.get_row(self.current.y);
             let current_x = self.current.x as usize;
@@ -406,7 +551,7 @@ mod parser {
         separated_list1(newline, parse_grid_cells)(input)
     }
 
-    pub(crate) fn parse_input(input: &str) -> miette::Result<(Grid, Path)> {
+    pub fn parse_input(input: &str) -> miette::Result<(Grid, Path)> {
         // Parse grid
         let (input, grid) = match parse_grid(LocatedSpan::new(input)) {
             Ok((input, cells)) => {
@@ -519,8 +664,102 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
         Ok(())
     }
 
+    #[test]
+    fn test_push_matches_execute_move() -> miette::Result<()> {
+        let input = "\
+##########
+#..O..O.O#
+#......O.#
+#.OO..O.O#
+#..O@..O.#
+#O#..O...#
+#O..O..O.#
+#.OO.O.OO#
+#....O...#
+##########
+
+<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v";
+        let (mut transposed, path) = parse_input(input)?;
+        let (x, y) = find_robot(&transposed)?;
+        let mut direct = transposed.clone();
+        let mut turning_robot = Robot::new(x, y);
+        let mut pushing_robot = Robot::new(x, y);
+
+        for &direction in &path.0 {
+            turning_robot.execute_move(&mut transposed, direction)?;
+            pushing_robot.push(&mut direct, direction)?;
+            assert_eq!(transposed.to_string(), direct.to_string());
+        }
+        assert_eq!(transposed.get_grid_gps(), direct.get_grid_gps());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_robot_count() {
+        let no_robot = "\
+#####
+#.O.#
+#####
+
+<>";
+        let error = process(no_robot).unwrap_err();
+        assert!(error.to_string().contains("No robot"));
+
+        let two_robots = "\
+#######
+#@.O.@#
+#######
+
+<>";
+        let error = process(two_robots).unwrap_err();
+        assert!(error.to_string().contains("found 2 at [(1, 1), (5, 1)]"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_grid_serde_round_trip() -> miette::Result<()> {
+        use miette::IntoDiagnostic;
+
+        let input = "\
+########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<";
+        let (grid, _) = parser::parse_input(input)?;
+
+        let json = serde_json::to_string(&grid).into_diagnostic()?;
+        // One string per row, exactly as the map is drawn
+        let rows = input.lines().take_while(|line| !line.is_empty());
+        assert_eq!(
+            serde_json::to_string(&rows.collect::<Vec<_>>()).into_diagnostic()?,
+            json
+        );
+
+        let restored: grid::Grid = serde_json::from_str(&json).into_diagnostic()?;
+        assert_eq!(grid.to_string(), restored.to_string());
+        assert_eq!((grid.width, grid.height), (restored.width, restored.height));
+        assert_eq!(grid.get_grid_gps(), restored.get_grid_gps());
+
+        // Restored cells are numbered by column and row, so another trip is exact
+        let again: grid::Grid =
+            serde_json::from_str(&serde_json::to_string(&restored).into_diagnostic()?)
+                .into_diagnostic()?;
+        assert_eq!(restored, again);
+
+        assert!(serde_json::from_str::<grid::Grid>(r###"["##","#"]"###).is_err());
+        assert!(serde_json::from_str::<grid::Grid>(r###"["#x"]"###).is_err());
+        Ok(())
+    }
+
     #[cfg(test)]
-    mod tests {
+    mod grid_tests {
         use crate::part1::{
             grid::{Grid, GridCell},
             robot::{Direction, Robot},
@@ -710,29 +949,39 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
             println!("\nFinal grid:");
             grid.display_grid();
 
-            // Compare final state with expected
-            match direction {
-                Direction::Up | Direction::Down => {
-                    // For vertical movements, check the specified column
-                    for (i, &expected) in expected_cells.iter().enumerate() {
-                        assert_eq!(
-                            grid.cells[i][0].cell, expected,
-                            "Mismatch at position {}: expected '{}', got '{}'",
-                            i, expected, grid.cells[i][0].cell
-                        );
-                    }
-                }
-                _ => {
-                    // For horizontal movements, check the specified row
-                    for (i, &expected) in expected_cells.iter().enumerate() {
-                        assert_eq!(
-                            grid.cells[0][i].cell, expected,
-                            "Mismatch at position {}: expected '{}', got '{}'",
-                            i, expected, grid.cells[0][i].cell
-                        );
-                    }
+            // Vertical cases list their single column, horizontal ones their single row
+            let mut expected = Grid {
+                cells: initial_cells,
+                width,
+                height,
+            };
+            for (i, &cell) in expected_cells.iter().enumerate() {
+                match direction {
+                    Direction::Up | Direction::Down => expected.cells[i][0].cell = cell,
+                    _ => expected.cells[0][i].cell = cell,
                 }
             }
+
+            let diff = grid.diff(&expected);
+            assert!(diff.is_empty(), "(x, y, got, expected): {:?}", diff);
+        }
+
+        #[test]
+        fn test_diff() -> miette::Result<()> {
+            let grid = Grid::try_from(vec!["#@.O#".to_string(), "#...#".to_string()])?;
+            assert!(grid.diff(&grid.clone()).is_empty());
+
+            let moved = Grid::try_from(vec!["#.@O#".to_string(), "#...#".to_string()])?;
+            assert_eq!(vec![(1, 0, '@', '.'), (2, 0, '.', '@')], grid.diff(&moved));
+
+            let boxed = Grid::try_from(vec!["#@.O#".to_string(), "#.O.#".to_string()])?;
+            assert_eq!(vec![(2, 1, '.', 'O')], grid.diff(&boxed));
+
+            // A missing row shows up as blanks
+            let short = Grid::try_from(vec!["#@.O#".to_string()])?;
+            assert_eq!(5, grid.diff(&short).len());
+            assert_eq!((0, 1, '#', ' '), grid.diff(&short)[0]);
+            Ok(())
         }
     }
 }
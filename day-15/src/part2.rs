@@ -1,20 +1,11 @@
 use crate::part2::robot::*;
 
 #[tracing::instrument]
-pub fn process(_input: &str) -> miette::Result<String> {
-    let (mut grid, path) = parser::parse_input(_input)?;
-
-    let (robot_x, robot_y) = grid
-        .cells
-        .iter()
-        .enumerate()
-        .find_map(|(y, row)| {
-            row.iter()
-                .enumerate()
-                .find(|(_, cell)| cell.is_robot())
-                .map(|(x, _)| (x as i32, y as i32))
-        })
-        .expect("Robot not found in grid");
+pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &::grid::normalize(input);
+    let (mut grid, path) = parser::parse_input(input)?;
+
+    let (robot_x, robot_y) = grid::find_robot(&grid)?;
 
     let mut robot = Robot::new(robot_x, robot_y);
 
@@ -70,10 +61,14 @@ mod error {
 }
 
 mod grid {
-    use crate::part2::parser::{BOX, EMPTY, ROBOT, WALL};
+    use crate::part2::{
+        error::GameError,
+        parser::{BOX, EMPTY, ROBOT, WALL},
+    };
     use std::fmt::{self, Display, Formatter};
 
     #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub(crate) struct GridCell {
         pub(crate) x: i32,
         pub(crate) y: i32,
@@ -102,13 +97,69 @@ mod grid {
         }
     }
 
+    /// With the `serde` feature a grid snapshots as one string per row, just as it
+    /// prints, rather than a struct per cell
     #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde::Serialize, serde::Deserialize),
+        serde(into = "Vec<String>", try_from = "Vec<String>")
+    )]
     pub(crate) struct Grid {
         pub(crate) cells: Vec<Vec<GridCell>>,
         pub(crate) width: i32,
         pub(crate) height: i32,
     }
 
+    impl From<Grid> for Vec<String> {
+        fn from(grid: Grid) -> Self {
+            grid.cells
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.cell).collect())
+                .collect()
+        }
+    }
+
+    /// Rebuilds a grid from its rows, numbering cells by column and row
+    impl TryFrom<Vec<String>> for Grid {
+        type Error = GameError;
+
+        fn try_from(rows: Vec<String>) -> Result<Self, Self::Error> {
+            let width = rows.first().map_or(0, |row| row.chars().count());
+
+            let cells = rows
+                .iter()
+                .enumerate()
+                .map(|(y, row)| {
+                    if row.chars().count() != width {
+                        return Err(GameError::Parse(format!(
+                            "row {} is not {} cells wide",
+                            y, width
+                        )));
+                    }
+                    row.chars()
+                        .enumerate()
+                        .map(|(x, cell)| match cell {
+                            ROBOT | WALL | BOX | EMPTY => {
+                                Ok(GridCell::new(x as i32, y as i32, cell))
+                            }
+                            _ => Err(GameError::Parse(format!(
+                                "invalid cell '{}' at ({}, {})",
+                                cell, x, y
+                            ))),
+                        })
+                        .collect()
+                })
+                .collect::<Result<Vec<Vec<GridCell>>, _>>()?;
+
+            Ok(Grid {
+                cells,
+                width: width as i32,
+                height: rows.len() as i32,
+            })
+        }
+    }
+
     impl Display for Grid {
         fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
             for row in &self.cells {
@@ -145,28 +196,21 @@ mod grid {
         }
 
         pub(crate) fn transpose(&mut self) -> miette::Result<()> {
-            let height = self.height as usize;
-            let width = self.width as usize;
-
-            let mut transposed = vec![vec![]; width];
-
-            for (j, row) in transposed.iter_mut().enumerate().take(width) {
-                for (i, cell) in self.cells.iter().enumerate().take(height) {
-                    let mut new_cell = cell[j].clone();
-                    new_cell.x = i as i32;
-                    new_cell.y = j as i32;
-                    row.push(new_cell);
+            self.cells = grid::matrix::transpose(&self.cells);
+            for (y, row) in self.cells.iter_mut().enumerate() {
+                for (x, cell) in row.iter_mut().enumerate() {
+                    cell.x = x as i32;
+                    cell.y = y as i32;
                 }
             }
 
-            self.cells = transposed;
             std::mem::swap(&mut self.width, &mut self.height);
             Ok(())
         }
 
         pub(crate) fn reverse_rows(&mut self) -> miette::Result<()> {
+            self.cells = grid::matrix::reverse_rows(&self.cells);
             for row in self.cells.iter_mut() {
-                row.reverse();
                 let width = row.len();
                 for (i, cell) in row.iter_mut().enumerate() {
                     cell.x = (width - 1 - i) as i32;
@@ -192,6 +236,33 @@ mod grid {
                 .sum()
         }
     }
+
+    /// Position of the one robot on the grid. No robot, or several as in a badly
+    /// doubled grid, is an error rather than a guess.
+    pub(crate) fn find_robot(grid: &Grid) -> miette::Result<(i32, i32)> {
+        let robots = grid
+            .cells
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.is_robot())
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect::<Vec<_>>();
+
+        match robots[..] {
+            [robot] => Ok(robot),
+            [] => Err(GameError::Parse(format!("No robot ({ROBOT}) found in the grid")).into()),
+            _ => Err(GameError::Parse(format!(
+                "Expected one robot ({ROBOT}) but found {} at {:?}",
+                robots.len(),
+                robots
+            ))
+            .into()),
+        }
+    }
 }
 
 mod robot {
@@ -519,8 +590,71 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
         Ok(())
     }
 
+    #[test]
+    fn test_process_robot_count() {
+        let no_robot = "\
+#####
+#.O.#
+#####
+
+<>";
+        let error = process(no_robot).unwrap_err();
+        assert!(error.to_string().contains("No robot"));
+
+        let two_robots = "\
+#######
+#@.O.@#
+#######
+
+<>";
+        let error = process(two_robots).unwrap_err();
+        assert!(error.to_string().contains("found 2 at [(1, 1), (5, 1)]"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_grid_serde_round_trip() -> miette::Result<()> {
+        use miette::IntoDiagnostic;
+
+        let input = "\
+########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<";
+        let (grid, _) = parser::parse_input(input)?;
+
+        let json = serde_json::to_string(&grid).into_diagnostic()?;
+        // One string per row, exactly as the map is drawn
+        let rows = input.lines().take_while(|line| !line.is_empty());
+        assert_eq!(
+            serde_json::to_string(&rows.collect::<Vec<_>>()).into_diagnostic()?,
+            json
+        );
+
+        let restored: grid::Grid = serde_json::from_str(&json).into_diagnostic()?;
+        assert_eq!(grid.to_string(), restored.to_string());
+        assert_eq!((grid.width, grid.height), (restored.width, restored.height));
+        assert_eq!(grid.get_grid_gps(), restored.get_grid_gps());
+
+        // Restored cells are numbered by column and row, so another trip is exact
+        let again: grid::Grid =
+            serde_json::from_str(&serde_json::to_string(&restored).into_diagnostic()?)
+                .into_diagnostic()?;
+        assert_eq!(restored, again);
+
+        assert!(serde_json::from_str::<grid::Grid>(r###"["##","#"]"###).is_err());
+        assert!(serde_json::from_str::<grid::Grid>(r###"["#x"]"###).is_err());
+        Ok(())
+    }
+
     #[cfg(test)]
-    mod tests {
+    mod grid_tests {
         use crate::part2::{
             grid::{Grid, GridCell},
             robot::{Direction, Robot},
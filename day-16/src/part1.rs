@@ -1,7 +1,148 @@
+use std::collections::HashSet;
+
 use graph::FastGraph;
-use types::{CellType, Direction, Position};
+use grid::{Direction, Pos};
+use petgraph::{
+    algo::dijkstra,
+    graph::NodeIndex,
+    visit::{EdgeRef, Reversed},
+};
+use types::{CellType, NodeState};
 
 pub fn process(input: &str) -> miette::Result<String> {
+    Ok(process_typed(input)?.to_string())
+}
+
+/// Lowest score a reindeer can get from the start to the end tile
+pub fn process_typed(input: &str) -> miette::Result<u64> {
+    let input: &str = &grid::normalize(input);
+    Ok(best_route(input)?.0)
+}
+
+/// The maze with one lowest scoring route drawn in, like the puzzle's illustration:
+/// every tile the reindeer steps off shows the way it went, apart from `S`
+pub fn render_path(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
+    let (_, route) = best_route(input)?;
+    let mut cells = input
+        .lines()
+        .map(|line| line.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    for (from, to) in route.iter().zip(route.iter().skip(1)) {
+        let Some(dir) = Direction::ALL
+            .into_iter()
+            .find(|&dir| from.step(dir) == Some(*to))
+        else {
+            // Turning on the spot
+            continue;
+        };
+        let cell = &mut cells[from.y][from.x];
+        if *cell != 'S' {
+            *cell = match dir {
+                Direction::Up => '^',
+                Direction::Right => '>',
+                Direction::Down => 'v',
+                Direction::Left => '<',
+            };
+        }
+    }
+
+    Ok(cells
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Lowest score along with the tile under each node of a route that gets it, so
+/// turning on the spot repeats a tile
+fn best_route(input: &str) -> miette::Result<(u64, Vec<Pos>)> {
+    let (fast_graph, start_node, end_pos) = build_graph(input)?;
+
+    // Use A* to find shortest path
+    let (cost, path) = petgraph::algo::astar(
+        &fast_graph.graph,
+        start_node,
+        |n| fast_graph.graph[n].cell_type == CellType::End,
+        |e| *e.weight(),
+        |n| fast_graph.graph[n].pos.manhattan(end_pos) as u64,
+    )
+    .ok_or(error::PuzzleError::NoPath)?;
+
+    Ok((
+        cost,
+        path.into_iter().map(|n| fast_graph.graph[n].pos).collect(),
+    ))
+}
+
+/// Lowest score along with how many tiles lie on at least one path with that score,
+/// answering both parts from one search out from the start and one back from the end
+pub fn solve(input: &str) -> miette::Result<(u64, usize)> {
+    let input: &str = &grid::normalize(input);
+    let (mut fast_graph, start_node, end_pos) = build_graph(input)?;
+    let graph = &mut fast_graph.graph;
+
+    // Every facing on the end tile feeds one sink, so a single search back covers them all
+    let ends = graph
+        .node_indices()
+        .filter(|&n| graph[n].cell_type == CellType::End)
+        .collect::<Vec<_>>();
+    let sink = graph.add_node(NodeState {
+        pos: end_pos,
+        cell_type: CellType::End,
+    });
+    for end in ends {
+        graph.add_edge(end, sink, 0);
+    }
+
+    let forward = dijkstra(&*graph, start_node, None, |e| *e.weight());
+    let best = *forward.get(&sink).ok_or(error::PuzzleError::NoPath)?;
+    let backward = dijkstra(Reversed(&*graph), sink, None, |e| *e.weight());
+
+    // A node is on a best path when getting there and getting on to the end adds up
+    let tiles = graph
+        .node_indices()
+        .filter(|&n| n != sink)
+        .filter(|n| match (forward.get(n), backward.get(n)) {
+            (Some(to), Some(from)) => to + from == best,
+            _ => false,
+        })
+        .map(|n| graph[n].pos)
+        .collect::<HashSet<_>>()
+        .len();
+
+    Ok((best, tiles))
+}
+
+/// Cost of stepping one tile forward
+const STEP_COST: u64 = 1;
+/// Cost of turning a quarter turn on the spot
+const TURN_COST: u64 = 1000;
+
+/// A tile together with the way the reindeer faces on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodePosition {
+    pub pos: Pos,
+    pub dir: Direction,
+}
+
+/// Every move a reindeer can make from `pos` facing `dir`, with its cost: one step
+/// forward, or a quarter turn either way. Walls and the far edges of the maze are
+/// left for the caller to rule out.
+pub fn moves_from(pos: Pos, dir: Direction) -> impl Iterator<Item = (NodePosition, u64)> {
+    let forward = pos
+        .step(dir)
+        .map(|pos| (NodePosition { pos, dir }, STEP_COST));
+    let turns =
+        [dir.turn_left(), dir.turn_right()].map(|dir| (NodePosition { pos, dir }, TURN_COST));
+
+    forward.into_iter().chain(turns)
+}
+
+/// Graph over every open tile and facing, with the start node (facing right) and
+/// the end tile
+fn build_graph(input: &str) -> miette::Result<(FastGraph, NodeIndex, Pos)> {
     let grid = parser::parse_grid(input)?;
     let (width, height) = grid.dimensions();
     let mut fast_graph = FastGraph::new(width, height);
@@ -9,7 +150,7 @@ pub fn process(input: &str) -> miette::Result<String> {
     // Create nodes
     for (pos, cell_type) in grid.iter_positions() {
         if cell_type != CellType::Wall {
-            for dir in Direction::all() {
+            for dir in Direction::ALL {
                 fast_graph.add_node(pos, cell_type, dir);
             }
         }
@@ -26,76 +167,11 @@ pub fn process(input: &str) -> miette::Result<String> {
         .get_node(start_pos, Direction::Right)
         .ok_or(error::PuzzleError::InvalidPosition(start_pos))?;
 
-    // Use A* to find shortest path
-    let result = petgraph::algo::astar(
-        &fast_graph.graph,
-        start_node,
-        |n| fast_graph.graph[n].cell_type == CellType::End,
-        |e| *e.weight(),
-        |n| manhattan_distance(fast_graph.graph[n].pos, end_pos),
-    );
-
-    result
-        .map(|(cost, _)| cost.to_string())
-        .ok_or_else(|| error::PuzzleError::NoPath.into())
-}
-
-fn manhattan_distance(pos1: Position, pos2: Position) -> u32 {
-    (pos1.x().abs_diff(pos2.x()) + pos1.y().abs_diff(pos2.y())) as u32
+    Ok((fast_graph, start_node, end_pos))
 }
 
 mod types {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub struct Position(u32);
-
-    impl Position {
-        pub fn new(x: usize, y: usize) -> Self {
-            debug_assert!(
-                x < (1 << 16) && y < (1 << 16),
-                "Position coordinates must fit in 16 bits"
-            );
-            Self(((y as u32) << 16) | (x as u32))
-        }
-
-        pub fn x(&self) -> usize {
-            (self.0 & 0xFFFF) as usize
-        }
-
-        pub fn y(&self) -> usize {
-            (self.0 >> 16) as usize
-        }
-    }
-
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub enum Direction {
-        Up,
-        Down,
-        Left,
-        Right,
-    }
-
-    impl Direction {
-        pub const fn as_index(self) -> usize {
-            match self {
-                Self::Up => 0,
-                Self::Down => 1,
-                Self::Left => 2,
-                Self::Right => 3,
-            }
-        }
-
-        pub fn all() -> [Direction; 4] {
-            [Self::Up, Self::Down, Self::Left, Self::Right]
-        }
-
-        pub fn turn_cost(&self, new_direction: Direction) -> u32 {
-            if *self == new_direction {
-                0
-            } else {
-                1000
-            }
-        }
-    }
+    use grid::Pos;
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum CellType {
@@ -121,13 +197,14 @@ mod types {
 
     #[derive(Debug, Clone, Copy)]
     pub struct NodeState {
-        pub pos: Position,
+        pub pos: Pos,
         pub cell_type: CellType,
     }
 }
 
 mod error {
-    use crate::part1::types::{CellType, Position};
+    use crate::part1::types::CellType;
+    use grid::Pos;
     use miette::Diagnostic;
     use thiserror::Error;
 
@@ -140,7 +217,7 @@ mod error {
         InvalidCell(char),
 
         #[error("Invalid position: {0:?}")]
-        InvalidPosition(Position),
+        InvalidPosition(Pos),
 
         #[error("Could not find cell of type {0:?}")]
         CellNotFound(CellType),
@@ -151,10 +228,8 @@ mod error {
 }
 
 mod parser {
-    use crate::part1::{
-        error::PuzzleError,
-        types::{CellType, Position},
-    };
+    use crate::part1::{error::PuzzleError, types::CellType};
+    use grid::Pos;
     use nom::{
         character::complete::{line_ending, one_of},
         combinator::map_res,
@@ -173,22 +248,22 @@ mod parser {
             (width, height)
         }
 
-        pub fn find_special_cell(&self, target: CellType) -> Result<Position, PuzzleError> {
+        pub fn find_special_cell(&self, target: CellType) -> Result<Pos, PuzzleError> {
             for (y, row) in self.cells.iter().enumerate() {
                 for (x, &cell) in row.iter().enumerate() {
                     if cell == target {
-                        return Ok(Position::new(x, y));
+                        return Ok(Pos::new(x, y));
                     }
                 }
             }
             Err(PuzzleError::CellNotFound(target))
         }
 
-        pub fn iter_positions(&self) -> impl Iterator<Item = (Position, CellType)> + '_ {
+        pub fn iter_positions(&self) -> impl Iterator<Item = (Pos, CellType)> + '_ {
             self.cells.iter().enumerate().flat_map(|(y, row)| {
                 row.iter()
                     .enumerate()
-                    .map(move |(x, &cell)| (Position::new(x, y), cell))
+                    .map(move |(x, &cell)| (Pos::new(x, y), cell))
             })
         }
     }
@@ -205,14 +280,13 @@ mod parser {
 }
 
 mod graph {
-    use crate::part1::types::*;
+    use crate::part1::{moves_from, types::*};
+    use grid::{Direction, Pos};
     use petgraph::graph::{DiGraph, NodeIndex};
 
-    const MOVEMENT_COST: u32 = 1;
-
     pub struct FastGraph {
         // Core graph for pathfinding
-        pub graph: DiGraph<NodeState, u32>,
+        pub graph: DiGraph<NodeState, u64>,
         // Fast lookup from position+direction to node index
         nodes: Vec<Option<NodeIndex>>,
         width: usize,
@@ -230,13 +304,13 @@ mod graph {
             }
         }
 
-        fn get_index(&self, pos: Position, dir: Direction) -> usize {
-            (pos.y() * self.width + pos.x()) * 4 + dir.as_index()
+        fn get_index(&self, pos: Pos, dir: Direction) -> usize {
+            (pos.y * self.width + pos.x) * 4 + dir.index()
         }
 
         pub fn add_node(
             &mut self,
-            pos: Position,
+            pos: Pos,
             cell_type: CellType,
             direction: Direction,
         ) -> NodeIndex {
@@ -247,7 +321,7 @@ mod graph {
             node_idx
         }
 
-        pub fn get_node(&self, pos: Position, dir: Direction) -> Option<NodeIndex> {
+        pub fn get_node(&self, pos: Pos, dir: Direction) -> Option<NodeIndex> {
             let idx = self.get_index(pos, dir);
             self.nodes.get(idx).copied().flatten()
         }
@@ -258,14 +332,14 @@ mod graph {
             // Collect all edges first
             for y in 0..self.height {
                 for x in 0..self.width {
-                    let pos = Position::new(x, y);
-                    for from_dir in Direction::all() {
+                    let pos = Pos::new(x, y);
+                    for from_dir in Direction::ALL {
                         if let Some(from_idx) = self.get_node(pos, from_dir) {
-                            // Try all possible moves from this position
-                            let possible_moves = get_possible_moves(pos, self.width, self.height);
-                            for (next_pos, to_dir) in possible_moves {
-                                if let Some(to_idx) = self.get_node(next_pos, to_dir) {
-                                    let cost = MOVEMENT_COST + from_dir.turn_cost(to_dir);
+                            for (next, cost) in moves_from(pos, from_dir) {
+                                if !next.pos.in_bounds(self.width, self.height) {
+                                    continue;
+                                }
+                                if let Some(to_idx) = self.get_node(next.pos, next.dir) {
                                     edges.push((from_idx, to_idx, cost));
                                 }
                             }
@@ -280,34 +354,29 @@ mod graph {
             }
         }
     }
-
-    fn get_possible_moves(
-        pos: Position,
-        width: usize,
-        height: usize,
-    ) -> Vec<(Position, Direction)> {
-        let mut moves = Vec::with_capacity(4);
-
-        if pos.x() > 0 {
-            moves.push((Position::new(pos.x() - 1, pos.y()), Direction::Left));
-        }
-        if pos.x() + 1 < width {
-            moves.push((Position::new(pos.x() + 1, pos.y()), Direction::Right));
-        }
-        if pos.y() > 0 {
-            moves.push((Position::new(pos.x(), pos.y() - 1), Direction::Up));
-        }
-        if pos.y() + 1 < height {
-            moves.push((Position::new(pos.x(), pos.y() + 1), Direction::Down));
-        }
-
-        moves
-    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::part1::process;
+    use crate::part1::{moves_from, process, process_typed, render_path, solve, NodePosition};
+    use grid::{Direction, Pos};
+
+    const EXAMPLE_SMALL: &str = "\
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
 
     #[test]
     fn test_simple_path() -> miette::Result<()> {
@@ -330,6 +399,19 @@ SE";
         Ok(())
     }
 
+    #[test]
+    fn test_process_typed() -> miette::Result<()> {
+        let input = "\
+###
+#S#
+#.#
+#E#
+###";
+        assert_eq!(1002, process_typed(input)?);
+        assert_eq!(process_typed(input)?.to_string(), process(input)?);
+        Ok(())
+    }
+
     #[test]
     fn test_complex_maze() -> miette::Result<()> {
         let input = "\
@@ -354,4 +436,115 @@ SE";
         assert_eq!("11048", process(input)?);
         Ok(())
     }
+
+    #[test]
+    fn test_render_path() -> miette::Result<()> {
+        let rendered = render_path(EXAMPLE_SMALL)?;
+        assert!(rendered.contains('S'));
+        assert!(rendered.contains('E'));
+
+        // 7036 is 7 turns and 36 steps, and every step but the one off S leaves a mark
+        let marked = rendered.chars().filter(|c| "^>v<".contains(*c)).count();
+        assert_eq!(35, marked);
+
+        // Only open tiles are drawn on, so the maze itself is untouched
+        for (original, drawn) in EXAMPLE_SMALL.chars().zip(rendered.chars()) {
+            assert!(original == drawn || (original == '.' && "^>v<".contains(drawn)));
+        }
+        assert_eq!(EXAMPLE_SMALL.len(), rendered.len());
+
+        // Straight along the corridor
+        assert_eq!("#S>E#", render_path("#S.E#")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_moves_from() {
+        let pos = Pos::new(3, 5);
+        let moves = moves_from(pos, Direction::Up).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (
+                    NodePosition {
+                        pos: Pos::new(3, 4),
+                        dir: Direction::Up
+                    },
+                    1
+                ),
+                (
+                    NodePosition {
+                        pos,
+                        dir: Direction::Left
+                    },
+                    1000
+                ),
+                (
+                    NodePosition {
+                        pos,
+                        dir: Direction::Right
+                    },
+                    1000
+                ),
+            ],
+            moves
+        );
+
+        // Nowhere to step off the top edge, but turning is still allowed
+        let moves = moves_from(Pos::new(3, 0), Direction::Up).collect::<Vec<_>>();
+        assert_eq!(2, moves.len());
+        assert!(moves.iter().all(|&(_, cost)| cost == 1000));
+    }
+
+    #[test]
+    fn test_long_staircase() -> miette::Result<()> {
+        // A one-wide staircase climbing up and to the right, so every move after the
+        // first one also turns
+        const STEPS: usize = 250;
+        let size = STEPS + 3;
+        let mut maze = vec![vec!['#'; size]; size];
+        for i in 0..STEPS {
+            maze[STEPS + 1 - i][1 + i] = '.';
+            maze[STEPS + 1 - i][2 + i] = '.';
+        }
+        maze[STEPS + 1][1] = 'S';
+        maze[1][STEPS + 1] = 'E';
+        let input = maze
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let moves = 2 * STEPS as u64;
+        let expected = moves + 1000 * (moves - 1);
+        assert_eq!(expected, process_typed(&input)?);
+        assert_eq!((expected, 2 * STEPS + 1), solve(&input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve() -> miette::Result<()> {
+        assert_eq!((7036, 45), solve(EXAMPLE_SMALL)?);
+        assert_eq!("7036", process(EXAMPLE_SMALL)?);
+
+        let input = "\
+#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
+        assert_eq!((11048, 64), solve(input)?);
+        Ok(())
+    }
 }
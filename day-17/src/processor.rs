@@ -0,0 +1,809 @@
+use miette::miette;
+use std::collections::HashSet;
+use std::fmt;
+use tracing::trace;
+
+pub type Program = Vec<usize>;
+pub type RegisterValues = Vec<u64>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Register(u64);
+
+impl Register {
+    fn new(val: u64) -> Self {
+        Self(val)
+    }
+
+    pub fn read(&self) -> u64 {
+        self.0
+    }
+
+    pub fn write(&mut self, val: u64) {
+        self.0 = val;
+    }
+}
+
+#[derive(Debug)]
+pub struct Processor {
+    pub register_a: Register,
+    pub register_b: Register,
+    pub register_c: Register,
+    pub program: Program,
+    pub pc: usize,
+    pub output: Vec<usize>,
+}
+
+/// Why [`Processor::run_with_reason`] stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltReason {
+    /// The PC moved past the end of the program
+    Halted,
+    /// Ran `MAX_STEPS` instructions without halting
+    StepLimit,
+    /// Produced `MAX_OUTPUT` values without halting
+    OutputLimit,
+    /// Came back to a PC and register state it had already been in, so it would
+    /// repeat itself forever
+    Cycle,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Instruction(OpCode, Operand);
+
+#[derive(Debug, Clone, Copy)]
+pub struct OpCode(pub usize);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Operand(pub usize);
+
+impl Processor {
+    pub const MAX_STEPS: usize = 1000;
+    pub const MAX_OUTPUT: usize = 100;
+
+    // INIT
+    pub fn new(init: RegisterValues, program: Program) -> Self {
+        Self {
+            register_a: Register::new(init[0]),
+            register_b: Register::new(init[1]),
+            register_c: Register::new(init[2]),
+            program,
+            pc: 0,
+            output: Vec::new(),
+        }
+    }
+
+    // FETCH
+    fn fetch(&self) -> miette::Result<Instruction> {
+        let slice = self
+            .program
+            .get(self.pc..self.pc + 2)
+            .ok_or(miette!("Failed to fetch instruction"))?;
+        Ok(Instruction(OpCode(slice[0]), Operand(slice[1])))
+    }
+
+    // DECODE & EXECUTE
+    fn decode_execute(&mut self, instruction: Instruction) -> miette::Result<()> {
+        match instruction {
+            // 'adv' division: divide <a> by 2^<combo operand> and write the result to <a>
+            Instruction(OpCode(0), Operand(operand)) => {
+                let num = self.register_a.read();
+                let divisor = self.power_of_two(operand)?;
+                self.register_a.write(num / divisor);
+                self.pc += 2;
+                Ok(())
+            }
+            // 'bxl' bitwise XOR: bitwise XOR <b> and <literal operand> and write the result to <b>
+            Instruction(OpCode(1), Operand(operand)) => {
+                let val = self.register_b.read();
+                let result = val ^ operand as u64;
+                self.register_b.write(result);
+                self.pc += 2;
+                Ok(())
+            }
+            // 'bst' modulo 8: <combo operand> modulo 8 and write the result to <b>
+            Instruction(OpCode(2), Operand(operand)) => {
+                let val = self.get_combo(operand)?;
+                let result = val % 8;
+                self.register_b.write(result);
+                self.pc += 2;
+                Ok(())
+            }
+            // 'jnz' jump not zero: if <a> is not zero, jump to <literal operand> (no PC increment)
+            Instruction(OpCode(3), Operand(operand)) => {
+                if self.register_a.read() != 0 {
+                    self.pc = operand;
+                } else {
+                    self.pc += 2;
+                }
+                Ok(())
+            }
+            // 'bxc' bitwise XOR: bitwise XOR <b> and <c> and write the result to <b>
+            Instruction(OpCode(4), Operand(_operand)) => {
+                let val_1 = self.register_b.read();
+                let val_2 = self.register_c.read();
+                let result = val_1 ^ val_2;
+                self.register_b.write(result);
+                self.pc += 2;
+                Ok(())
+            }
+            // 'out' output: output <combo operand> modulo 8 (csv appended to output)
+            Instruction(OpCode(5), Operand(operand)) => {
+                let val = self.get_combo(operand)?;
+                let result = val % 8;
+                self.output.push(result as usize);
+                self.pc += 2;
+                Ok(())
+            }
+            // 'bdv' division: divide <a> by 2^<combo operand> and write the result to <b>
+            Instruction(OpCode(6), Operand(operand)) => {
+                let num = self.register_a.read();
+                let divisor = self.power_of_two(operand)?;
+                self.register_b.write(num / divisor);
+                self.pc += 2;
+                Ok(())
+            }
+            // 'cdv' division: divide <a> by 2^<combo operand> and write the result to <c>
+            Instruction(OpCode(7), Operand(operand)) => {
+                let num = self.register_a.read();
+                let divisor = self.power_of_two(operand)?;
+                self.register_c.write(num / divisor);
+                self.pc += 2;
+                Ok(())
+            }
+            _ => Err(miette!(
+                "Invalid instruction {:?} at PC {}",
+                instruction,
+                self.pc
+            )),
+        }
+    }
+
+    fn get_combo(&self, value: usize) -> miette::Result<u64> {
+        match value {
+            0..=3 => Ok(value as u64),
+            4 => Ok(self.register_a.read()),
+            5 => Ok(self.register_b.read()),
+            6 => Ok(self.register_c.read()),
+            _ => Err(miette!("Invalid combo operand {} at PC {}", value, self.pc)),
+        }
+    }
+
+    /// 2^<combo operand>, the divisor shared by `adv`, `bdv` and `cdv`
+    fn power_of_two(&self, operand: usize) -> miette::Result<u64> {
+        let exponent = self.get_combo(operand)?;
+        u32::try_from(exponent)
+            .ok()
+            .and_then(|exponent| 2u64.checked_pow(exponent))
+            .ok_or(miette!(
+                "Power overflow: 2^{} exceeds maximum value",
+                exponent
+            ))
+    }
+
+    /// The program as one mnemonic per instruction, e.g. `adv 3`, `out (A%8)`, `jnz 0`.
+    /// Combo operands are shown as the register they read, literals as numbers.
+    pub fn disassemble(&self) -> Vec<String> {
+        self.program
+            .chunks_exact(2)
+            .map(|instruction| {
+                let (opcode, operand) = (instruction[0], instruction[1]);
+                match opcode {
+                    0 => format!("adv {}", combo_name(operand)),
+                    1 => format!("bxl {}", operand),
+                    2 => format!("bst ({}%8)", combo_name(operand)),
+                    3 => format!("jnz {}", operand),
+                    4 => "bxc".to_string(),
+                    5 => format!("out ({}%8)", combo_name(operand)),
+                    6 => format!("bdv {}", combo_name(operand)),
+                    7 => format!("cdv {}", combo_name(operand)),
+                    _ => format!("??? {},{}", opcode, operand),
+                }
+            })
+            .collect()
+    }
+
+    /// Everything printed so far, comma separated as the puzzle expects
+    pub fn output_csv(&self) -> String {
+        self.output
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// True once the PC has moved past the last instruction
+    pub fn halted(&self) -> bool {
+        self.pc + 1 >= self.program.len()
+    }
+
+    /// Executes exactly one instruction, returning the value it printed if it was an `out`.
+    /// Stepping a halted program is an error.
+    pub fn step(&mut self) -> miette::Result<Option<usize>> {
+        let instruction = self.fetch()?;
+        trace!("{}", self);
+
+        let printed = self.output.len();
+        self.decode_execute(instruction)?;
+
+        Ok(self.output.get(printed).copied())
+    }
+
+    /// Runs up to and including the next `out`, returning the value it printed, or
+    /// `None` once the program halts. Going `MAX_STEPS` instructions without printing
+    /// is an error rather than a hang.
+    pub fn run_until_output(&mut self) -> miette::Result<Option<usize>> {
+        for _ in 0..Processor::MAX_STEPS {
+            if self.halted() {
+                return Ok(None);
+            }
+            if let Some(value) = self.step()? {
+                return Ok(Some(value));
+            }
+        }
+
+        Err(miette::miette!(
+            "Ran {} instructions at pc {} without printing or halting",
+            Processor::MAX_STEPS,
+            self.pc
+        ))
+    }
+
+    pub fn run(&mut self) -> miette::Result<&Vec<usize>> {
+        Ok(self.run_with_reason()?.0)
+    }
+
+    /// Runs until the program stops, reporting why it stopped along with the output
+    pub fn run_with_reason(&mut self) -> miette::Result<(&Vec<usize>, HaltReason)> {
+        self.run_until(false)
+    }
+
+    /// Like [`run_with_reason`](Self::run_with_reason), but also stops with
+    /// [`HaltReason::Cycle`] as soon as the PC and registers repeat a state seen earlier
+    pub fn run_detecting_cycles(&mut self) -> miette::Result<(&Vec<usize>, HaltReason)> {
+        self.run_until(true)
+    }
+
+    fn run_until(&mut self, detect_cycles: bool) -> miette::Result<(&Vec<usize>, HaltReason)> {
+        let mut steps = 0;
+        let mut seen = HashSet::new();
+
+        let reason = loop {
+            if self.halted() {
+                break HaltReason::Halted;
+            }
+            if detect_cycles && !seen.insert(self.state()) {
+                break HaltReason::Cycle;
+            }
+            if steps >= Processor::MAX_STEPS {
+                break HaltReason::StepLimit;
+            }
+            if self.output.len() >= Processor::MAX_OUTPUT {
+                break HaltReason::OutputLimit;
+            }
+
+            self.step()?;
+            steps += 1;
+        };
+
+        Ok((&self.output, reason))
+    }
+
+    /// Everything that decides what the program does next
+    fn state(&self) -> (usize, u64, u64, u64) {
+        (
+            self.pc,
+            self.register_a.read(),
+            self.register_b.read(),
+            self.register_c.read(),
+        )
+    }
+}
+
+/// How a combo operand reads in the disassembly
+fn combo_name(operand: usize) -> String {
+    match operand {
+        0..=3 => operand.to_string(),
+        4 => "A".to_string(),
+        5 => "B".to_string(),
+        6 => "C".to_string(),
+        _ => format!("?{}", operand),
+    }
+}
+
+impl fmt::Display for Processor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PC: {:2} | Instruction: [{},{}] | A: {:10} | B: {:10} | C: {:10} | Out: {:?}",
+            self.pc,
+            self.program.get(self.pc).unwrap_or(&0),
+            self.program.get(self.pc + 1).unwrap_or(&0),
+            self.register_a.read(),
+            self.register_b.read(),
+            self.register_c.read(),
+            self.output
+        )
+    }
+}
+
+/// Turns mnemonic lines, as produced by [`Processor::disassemble`], back into a program
+pub fn assemble(text: &str) -> miette::Result<Program> {
+    let mut program = Program::new();
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let (mnemonic, operand) = line.split_once(' ').unwrap_or((line, ""));
+        let operand = operand.trim();
+
+        let instruction = match mnemonic {
+            "adv" => (0, parse_combo(operand)),
+            "bxl" => (1, parse_literal(operand)),
+            "bst" => (2, parse_combo(operand)),
+            "jnz" => (3, parse_literal(operand)),
+            "bxc" => (4, Some(0)),
+            "out" => (5, parse_combo(operand)),
+            "bdv" => (6, parse_combo(operand)),
+            "cdv" => (7, parse_combo(operand)),
+            _ => return Err(miette!("Unknown mnemonic in `{}`", line)),
+        };
+
+        match instruction {
+            (opcode, Some(operand)) => program.extend([opcode, operand]),
+            _ => return Err(miette!("Invalid operand in `{}`", line)),
+        }
+    }
+
+    Ok(program)
+}
+
+/// A combo operand written as `0`-`3`, `A`, `B` or `C`, optionally as `(A%8)`
+fn parse_combo(text: &str) -> Option<usize> {
+    let text = text
+        .strip_prefix('(')
+        .and_then(|text| text.strip_suffix("%8)"))
+        .unwrap_or(text);
+    match text {
+        "A" => Some(4),
+        "B" => Some(5),
+        "C" => Some(6),
+        _ => text.parse().ok().filter(|&value| value <= 3),
+    }
+}
+
+fn parse_literal(text: &str) -> Option<usize> {
+    text.parse().ok().filter(|&value| value <= 7)
+}
+
+/// The shape of a program that works through A a few bits per pass of one loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopInfo {
+    /// One pass as `(opcode, operand)` pairs, without the closing `jnz 0`
+    pub body: Vec<(usize, usize)>,
+    /// Combo operand the pass prints with its `out`, e.g. 5 for B
+    pub output: usize,
+    /// Bits of A the pass's `adv` drops
+    pub shift: u32,
+}
+
+/// Checks `program` is a single loop closed by `jnz 0`, whose body prints once and
+/// shifts A right by a constant with one `adv`, and breaks it down. `None` when the
+/// program has any other shape.
+pub fn analyze_loop(program: &[usize]) -> Option<LoopInfo> {
+    if !program.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let mut instructions = program
+        .chunks_exact(2)
+        .map(|instruction| (instruction[0], instruction[1]));
+    let (3, 0) = instructions.next_back()? else {
+        return None;
+    };
+    let body = instructions.collect::<Vec<_>>();
+
+    let only = |opcode: usize| {
+        let mut matching = body.iter().filter(|&&(op, _)| op == opcode);
+        match (matching.next(), matching.next()) {
+            (Some(&(_, operand)), None) => Some(operand),
+            _ => None,
+        }
+    };
+
+    if body.iter().any(|&(opcode, _)| opcode == 3) {
+        return None;
+    }
+    let output = only(5).filter(|&operand| operand <= 6)?;
+    let shift = only(0).filter(|operand| (1..=3).contains(operand))? as u32;
+
+    Some(LoopInfo {
+        body,
+        output,
+        shift,
+    })
+}
+
+pub(crate) fn parse_input(input: &str) -> miette::Result<(RegisterValues, Program)> {
+    let (_, (registers, program)) =
+        parser::parse_input(input).map_err(|e| miette!("Failed to parse input: {}", e))?;
+    Ok((registers, program))
+}
+
+mod parser {
+    use nom::{
+        branch::alt,
+        bytes::complete::tag,
+        character::complete::{char, digit1, line_ending, newline},
+        combinator::map_res,
+        multi::separated_list1,
+        sequence::{preceded, separated_pair},
+        IResult,
+    };
+
+    use super::{Program, RegisterValues};
+
+    pub fn parse_input(input: &str) -> IResult<&str, (RegisterValues, Program)> {
+        separated_pair(
+            parse_registers,
+            line_ending,
+            preceded(line_ending, parse_program),
+        )(input)
+    }
+
+    fn parse_registers(input: &str) -> IResult<&str, RegisterValues> {
+        separated_list1(
+            newline,
+            preceded(
+                alt((
+                    tag("Register A: "),
+                    tag("Register B: "),
+                    tag("Register C: "),
+                )),
+                map_res(digit1, str::parse::<u64>),
+            ),
+        )(input)
+    }
+
+    fn parse_program(input: &str) -> IResult<&str, Program> {
+        preceded(
+            tag("Program: "),
+            separated_list1(char(','), map_res(digit1, str::parse)),
+        )(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_log;
+
+    #[test]
+    fn test_parser() -> miette::Result<()> {
+        let input = "\
+Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+        let (regs, program) = parse_input(input)?;
+        assert_eq!((729, 0, 0), (regs[0], regs[1], regs[2]));
+        assert_eq!(vec![0, 1, 5, 4, 3, 0], program);
+        Ok(())
+    }
+
+    use rstest::rstest;
+
+    struct TestCase {
+        reg_a: u64,
+        reg_b: u64,
+        reg_c: u64,
+        program: Vec<usize>,
+        expected_output: Vec<usize>,
+        expected_reg_a: Option<u64>,
+        expected_reg_b: Option<u64>,
+    }
+
+    #[rstest]
+    #[test_log::test]
+    #[case(TestCase {
+        reg_a: 0,
+        reg_b: 0,
+        reg_c: 9,
+        program: vec![2, 6],
+        expected_output: vec![],
+        expected_reg_a: None,
+        expected_reg_b: Some(1),
+    })]
+    #[case(TestCase {
+        reg_a: 10,
+        reg_b: 0,
+        reg_c: 0,
+        program: vec![5, 0, 5, 1, 5, 4],
+        expected_output: vec![0, 1, 2],
+        expected_reg_a: None,
+        expected_reg_b: None,
+    })]
+    #[case(TestCase {
+        reg_a: 2024,
+        reg_b: 0,
+        reg_c: 0,
+        program: vec![0, 1, 5, 4, 3, 0],
+        expected_output: vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0],
+        expected_reg_a: Some(0),
+        expected_reg_b: None,
+    })]
+    #[case(TestCase {
+        reg_a: 0,
+        reg_b: 29,
+        reg_c: 0,
+        program: vec![1, 7],
+        expected_output: vec![],
+        expected_reg_a: None,
+        expected_reg_b: Some(26),
+    })]
+    #[case(TestCase {
+        reg_a: 0,
+        reg_b: 2024,
+        reg_c: 43690,
+        program: vec![4, 0],
+        expected_output: vec![],
+        expected_reg_a: None,
+        expected_reg_b: Some(44354),
+    })]
+    fn test_instructions(#[case] test_case: TestCase) -> miette::Result<()> {
+        let mut processor = Processor::new(
+            vec![test_case.reg_a, test_case.reg_b, test_case.reg_c],
+            test_case.program,
+        );
+
+        let output = processor.run()?;
+
+        if !test_case.expected_output.is_empty() {
+            assert_eq!(&test_case.expected_output, output);
+        }
+
+        if let Some(expected_a) = test_case.expected_reg_a {
+            assert_eq!(expected_a, processor.register_a.read());
+        }
+
+        if let Some(expected_b) = test_case.expected_reg_b {
+            assert_eq!(expected_b, processor.register_b.read());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_jnz_not_taken_halts() -> miette::Result<()> {
+        // out A, then a jnz that falls through because A is zero
+        let mut processor = Processor::new(vec![0, 0, 0], vec![5, 4, 3, 0]);
+        assert_eq!(&vec![0], processor.run()?);
+
+        // The PC stepped past the jnz rather than spinning on it until MAX_STEPS
+        assert_eq!(4, processor.pc);
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_csv() -> miette::Result<()> {
+        let mut processor = Processor::new(vec![2024, 0, 0], vec![0, 1, 5, 4, 3, 0]);
+        assert_eq!("", processor.output_csv());
+
+        processor.run()?;
+        assert_eq!("4,2,5,6,7,7,7,7,3,1,0", processor.output_csv());
+        Ok(())
+    }
+
+    #[test]
+    fn test_step() -> miette::Result<()> {
+        let mut processor = Processor::new(vec![10, 0, 0], vec![5, 0, 5, 1, 5, 4]);
+
+        let mut outputs = vec![];
+        while !processor.halted() {
+            let pc = processor.pc();
+            outputs.push(processor.step()?);
+            assert_eq!(pc + 2, processor.pc());
+        }
+        assert_eq!(vec![Some(0), Some(1), Some(2)], outputs);
+        assert!(processor.step().is_err());
+
+        // Only `out` prints anything
+        let mut processor = Processor::new(vec![10, 0, 0], vec![0, 1, 5, 4]);
+        assert_eq!(None, processor.step()?);
+        assert_eq!(Some(5), processor.step()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_until_output() -> miette::Result<()> {
+        let mut processor = Processor::new(vec![2024, 0, 0], vec![0, 1, 5, 4, 3, 0]);
+
+        let mut outputs = vec![];
+        while let Some(value) = processor.run_until_output()? {
+            outputs.push(value);
+        }
+        assert_eq!(vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0], outputs);
+        assert_eq!(outputs, processor.output);
+        assert!(processor.halted());
+        assert_eq!(None, processor.run_until_output()?);
+
+        // Spins on a jnz without printing anything
+        let mut processor = Processor::new(vec![1, 0, 0], vec![3, 0]);
+        assert!(processor.run_until_output().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_halt_reason() -> miette::Result<()> {
+        let mut processor = Processor::new(vec![0, 0, 0], vec![5, 4, 3, 0]);
+        assert_eq!(HaltReason::Halted, processor.run_with_reason()?.1);
+
+        // Spins on a jnz without printing anything
+        let mut processor = Processor::new(vec![1, 0, 0], vec![3, 0]);
+        assert_eq!(HaltReason::StepLimit, processor.run_with_reason()?.1);
+
+        // Prints A forever
+        let mut processor = Processor::new(vec![1, 0, 0], vec![5, 4, 3, 0]);
+        let (output, reason) = processor.run_with_reason()?;
+        assert_eq!(HaltReason::OutputLimit, reason);
+        assert_eq!(Processor::MAX_OUTPUT, output.len());
+
+        // Nothing to run at all
+        let mut processor = Processor::new(vec![0, 0, 0], vec![]);
+        assert_eq!(HaltReason::Halted, processor.run_with_reason()?.1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle_detection() -> miette::Result<()> {
+        // jnz 0 with A never changing jumps back to itself
+        let mut processor = Processor::new(vec![1, 0, 0], vec![3, 0]);
+        let (output, reason) = processor.run_detecting_cycles()?;
+        assert_eq!(HaltReason::Cycle, reason);
+        assert!(output.is_empty());
+        assert_eq!(0, processor.pc());
+
+        // Caught on the first repeat, long before the output limit
+        let mut processor = Processor::new(vec![1, 0, 0], vec![5, 4, 3, 0]);
+        let (output, reason) = processor.run_detecting_cycles()?;
+        assert_eq!(HaltReason::Cycle, reason);
+        assert_eq!(&vec![1], output);
+
+        // A program that halts is unaffected
+        let mut processor = Processor::new(vec![729, 0, 0], vec![0, 1, 5, 4, 3, 0]);
+        let (output, reason) = processor.run_detecting_cycles()?;
+        assert_eq!(HaltReason::Halted, reason);
+        assert_eq!(&vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0], output);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_combo_operand() {
+        // out 7: combo operand 7 is reserved
+        let mut processor = Processor::new(vec![0, 0, 0], vec![5, 4, 5, 7]);
+        let error = processor.run().unwrap_err();
+        assert!(error.to_string().contains("combo operand 7"));
+        assert_eq!(vec![0], processor.output);
+    }
+
+    #[test]
+    fn test_invalid_opcode() {
+        let mut processor = Processor::new(vec![0, 0, 0], vec![5, 4, 8, 0]);
+        let error = processor.run().unwrap_err();
+        assert!(error.to_string().contains("Invalid instruction"));
+        assert_eq!(2, processor.pc);
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let processor = Processor::new(vec![729, 0, 0], vec![0, 1, 5, 4, 3, 0]);
+        assert_eq!(vec!["adv 1", "out (A%8)", "jnz 0"], processor.disassemble());
+
+        let processor = Processor::new(
+            vec![0, 0, 0],
+            vec![2, 4, 1, 1, 7, 5, 4, 0, 0, 3, 1, 6, 5, 5, 3, 0],
+        );
+        assert_eq!(
+            vec![
+                "bst (A%8)",
+                "bxl 1",
+                "cdv B",
+                "bxc",
+                "adv 3",
+                "bxl 6",
+                "out (B%8)",
+                "jnz 0"
+            ],
+            processor.disassemble()
+        );
+
+        // Operands that mean nothing are still shown rather than dropped
+        let processor = Processor::new(vec![0, 0, 0], vec![6, 7, 9, 1]);
+        assert_eq!(vec!["bdv ?7", "??? 9,1"], processor.disassemble());
+    }
+
+    #[test]
+    fn test_assemble_round_trip() -> miette::Result<()> {
+        let text = "\
+bst (A%8)
+bxl 1
+cdv B
+bxc
+adv 3
+bxl 6
+out (B%8)
+jnz 0";
+        let program = assemble(text)?;
+        assert_eq!(
+            vec![2, 4, 1, 1, 7, 5, 4, 0, 0, 3, 1, 6, 5, 5, 3, 0],
+            program
+        );
+
+        let processor = Processor::new(vec![0, 0, 0], program);
+        assert_eq!(text, processor.disassemble().join("\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert!(assemble("mul A").is_err());
+        assert!(assemble("adv D").is_err());
+        assert!(assemble("bxl 8").is_err());
+        assert!(assemble("out").is_err());
+    }
+
+    #[test]
+    fn test_registers_beyond_32_bits() -> miette::Result<()> {
+        // adv 3 then out A: 2^40 + 5 shifted down by 3 bits is 2^37, which ends in 0
+        let mut processor =
+            Processor::new(vec![(1 << 40) + 5, 0, 0], assemble("adv 3\nout (A%8)")?);
+        assert_eq!(&vec![0], processor.run()?);
+        assert_eq!(1 << 37, processor.register_a.read());
+        Ok(())
+    }
+
+    #[test]
+    fn test_processor_display() {
+        let processor = Processor::new(vec![123, 456, 789], vec![0, 1, 2, 3]);
+        let display = format!("{}", processor);
+        assert!(display.contains("PC:  0"));
+        assert!(display.contains("A:        123"));
+        assert!(display.contains("B:        456"));
+        assert!(display.contains("C:        789"));
+        assert!(display.contains("Instruction: [0,1]"));
+    }
+
+    #[test]
+    fn test_analyze_loop() {
+        let info = analyze_loop(&[0, 3, 5, 4, 3, 0]).expect("the example is one loop");
+        assert_eq!(
+            LoopInfo {
+                body: vec![(0, 3), (5, 4)],
+                output: 4,
+                shift: 3,
+            },
+            info
+        );
+
+        // The usual puzzle shape prints B after mixing it with A and C
+        let info = analyze_loop(&[2, 4, 1, 1, 7, 5, 1, 5, 4, 0, 0, 3, 5, 5, 3, 0]).unwrap();
+        assert_eq!((7, 5, 3), (info.body.len(), info.output, info.shift));
+    }
+
+    #[rstest]
+    #[case::no_jnz(&[0, 3, 5, 4])]
+    #[case::jumps_elsewhere(&[0, 3, 5, 4, 3, 2])]
+    #[case::jnz_in_body(&[0, 3, 3, 0, 5, 4, 3, 0])]
+    #[case::no_out(&[0, 3, 3, 0])]
+    #[case::two_outs(&[0, 3, 5, 4, 5, 4, 3, 0])]
+    #[case::no_adv(&[5, 1, 3, 0])]
+    #[case::adv_by_register(&[0, 5, 5, 4, 3, 0])]
+    #[case::adv_by_zero(&[0, 0, 5, 4, 3, 0])]
+    #[case::odd_length(&[0, 3, 5, 4, 3])]
+    fn test_analyze_loop_rejects(#[case] program: &[usize]) {
+        assert_eq!(None, analyze_loop(program));
+    }
+}
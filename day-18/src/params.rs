@@ -0,0 +1,32 @@
+use grid::Pos;
+
+/// The memory space size and how many bytes fall before the path is checked, which
+/// differ between the worked example and the real puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// side length of the square memory space
+    pub dim: usize,
+    /// bytes that have fallen before the first search
+    pub bytes: usize,
+}
+
+impl Params {
+    /// The 7x7 worked example after 12 bytes
+    pub const EXAMPLE: Params = Params { dim: 7, bytes: 12 };
+    /// The real 71x71 puzzle after 1024 bytes
+    pub const PUZZLE: Params = Params {
+        dim: 71,
+        bytes: 1024,
+    };
+
+    /// The exit in the bottom right corner
+    pub const fn end(&self) -> Pos {
+        Pos::new(self.dim - 1, self.dim - 1)
+    }
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params::PUZZLE
+    }
+}
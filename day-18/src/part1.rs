@@ -1,95 +1,164 @@
+use grid::{astar_grid, Pos};
 use miette::miette;
 use petgraph::{
     algo::dijkstra,
     graph::{DiGraph, NodeIndex},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-#[cfg(test)]
-mod constants {
-    pub const DIM: usize = 7;
-    pub const BYTES: usize = 12;
-}
-
-#[cfg(not(test))]
-mod constants {
-    pub const DIM: usize = 71;
-    pub const BYTES: usize = 1024;
-}
+use crate::params::Params;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub struct Position(pub usize, pub usize);
+pub(crate) use graph::validate_coordinates;
 
-pub const START: Position = Position(0, 0);
-pub const END: Position = Position(constants::DIM - 1, constants::DIM - 1);
+pub const START: Pos = Pos::new(0, 0);
 
 type Grid = Vec<Vec<char>>;
 type Graph = DiGraph<char, ()>;
 
+/// Solves on the real puzzle's memory space, see [`process_with_params`] to pick another
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
-    let coords = parser::parse(input)?;
-    let graph = graph::create_graph(&coords)?;
+    process_with_params(input, Params::PUZZLE)
+}
 
-    let start_idx = graph::get_node_index(&graph, START)?;
-    let end_idx = graph::get_node_index(&graph, END)?;
+/// Steps to the exit once `params.bytes` bytes have fallen into a `params.dim` square
+#[tracing::instrument]
+pub fn process_with_params(input: &str, params: Params) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
+    let coords = parser::parse(input)?;
+    graph::validate_coordinates(&coords, params.dim)?;
 
-    let path = dijkstra(&graph, start_idx, Some(end_idx), |_| 1);
-    let distance = path
-        .get(&end_idx)
+    let fallen = &coords[..params.bytes.min(coords.len())];
+    let (distance, _) = shortest_path(fallen, params.dim, START, params.end())
         .ok_or_else(|| miette!("No path found to end position"))?;
 
     Ok(distance.to_string())
 }
 
+/// Steps on the shortest path from `start` to `end` on a `dim` x `dim` grid with a
+/// wall at each of `fallen`, along with the path itself
+pub fn shortest_path(
+    fallen: &[Pos],
+    dim: usize,
+    start: Pos,
+    end: Pos,
+) -> Option<(usize, Vec<Pos>)> {
+    let mut walls = vec![vec![false; dim]; dim];
+    for &Pos { x, y } in fallen {
+        walls[y][x] = true;
+    }
+
+    astar_grid(&walls, start, end, |_, _| 1, |pos| pos.manhattan(end))
+}
+
+/// Original petgraph-based solution, kept around for comparison with [`bfs_distance`].
+pub fn dijkstra_distance(coords: &[Pos], params: Params) -> miette::Result<usize> {
+    let graph = graph::create_graph(coords, params)?;
+
+    let start_idx = graph::get_node_index(&graph, START, params.dim)?;
+    let end_idx = graph::get_node_index(&graph, params.end(), params.dim)?;
+
+    let path = dijkstra(&graph, start_idx, Some(end_idx), |_| 1);
+    path.get(&end_idx)
+        .copied()
+        .ok_or_else(|| miette!("No path found to end position"))
+}
+
+/// Shortest number of steps from `start` to `end` on a `dim` x `dim` grid, or `None`
+/// if the walls cut the two off from each other or either lies outside the grid.
+pub fn bfs_distance(walls: &HashSet<Pos>, dim: usize, start: Pos, end: Pos) -> Option<usize> {
+    if !start.in_bounds(dim, dim) || !end.in_bounds(dim, dim) || walls.contains(&end) {
+        return None;
+    }
+
+    bfs(walls, dim, start)
+        .find(|&(pos, _)| pos == end)
+        .map(|(_, distance)| distance)
+}
+
+/// Open cells still connected to [`START`] once `params.bytes` bytes have fallen into
+/// a `params.dim` square, [`START`] included
+pub fn reachable_count(input: &str, params: Params) -> miette::Result<usize> {
+    let input: &str = &grid::normalize(input);
+    let coords = parser::parse(input)?;
+    graph::validate_coordinates(&coords, params.dim)?;
+
+    let walls: HashSet<Pos> = coords.iter().take(params.bytes).copied().collect();
+    Ok(bfs(&walls, params.dim, START).count())
+}
+
+/// Every open cell reachable from `start` with its distance, nearest first. Empty
+/// when `start` is a wall or off the grid.
+fn bfs(walls: &HashSet<Pos>, dim: usize, start: Pos) -> impl Iterator<Item = (Pos, usize)> + '_ {
+    let mut visited = vec![vec![false; dim]; dim];
+    let mut queue = VecDeque::new();
+
+    if start.in_bounds(dim, dim) && !walls.contains(&start) {
+        visited[start.y][start.x] = true;
+        queue.push_back((start, 0));
+    }
+
+    std::iter::from_fn(move || {
+        let (pos, distance) = queue.pop_front()?;
+
+        for next in pos.neighbors4() {
+            if !next.in_bounds(dim, dim) || visited[next.y][next.x] || walls.contains(&next) {
+                continue;
+            }
+
+            visited[next.y][next.x] = true;
+            queue.push_back((next, distance + 1));
+        }
+
+        Some((pos, distance))
+    })
+}
+
 mod graph {
     use super::*;
 
-    pub fn create_graph(coords: &[Position]) -> miette::Result<Graph> {
-        let mut grid = create_empty_grid();
-        validate_coordinates(coords)?;
-        place_walls(&mut grid, coords);
+    pub fn create_graph(coords: &[Pos], params: Params) -> miette::Result<Graph> {
+        let mut grid = create_empty_grid(params.dim);
+        validate_coordinates(coords, params.dim)?;
+        place_walls(&mut grid, coords, params.bytes);
 
         let (mut graph, nodes) = create_nodes(&grid);
-        add_edges(&grid, &mut graph, &nodes);
+        add_edges(&grid, &mut graph, &nodes, params.dim);
 
         Ok(graph)
     }
 
-    fn create_empty_grid() -> Grid {
-        vec![vec!['.'; constants::DIM]; constants::DIM]
+    fn create_empty_grid(dim: usize) -> Grid {
+        vec![vec!['.'; dim]; dim]
     }
 
-    fn validate_coordinates(coords: &[Position]) -> miette::Result<()> {
-        for Position(x, y) in coords {
-            if *x >= constants::DIM || *y >= constants::DIM {
+    pub fn validate_coordinates(coords: &[Pos], dim: usize) -> miette::Result<()> {
+        for Pos { x, y } in coords {
+            if *x >= dim || *y >= dim {
                 return Err(miette!(
                     "Coordinates ({}, {}) out of bounds (max: {})",
                     x,
                     y,
-                    constants::DIM - 1
+                    dim - 1
                 ));
             }
         }
         Ok(())
     }
 
-    fn place_walls(grid: &mut Grid, coords: &[Position]) {
-        coords
-            .iter()
-            .take(constants::BYTES)
-            .for_each(|Position(x, y)| {
-                grid[*y][*x] = '#';
-            });
+    fn place_walls(grid: &mut Grid, coords: &[Pos], bytes: usize) {
+        coords.iter().take(bytes).for_each(|Pos { x, y }| {
+            grid[*y][*x] = '#';
+        });
     }
 
     fn create_nodes(grid: &Grid) -> (Graph, HashMap<(usize, usize), NodeIndex>) {
         let mut graph = Graph::new();
         let mut nodes = HashMap::new();
 
-        for y in 0..constants::DIM {
-            for x in 0..constants::DIM {
-                let node = graph.add_node(grid[y][x]);
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                let node = graph.add_node(cell);
                 nodes.insert((x, y), node);
             }
         }
@@ -97,45 +166,42 @@ mod graph {
         (graph, nodes)
     }
 
-    fn add_edges(_grid: &Grid, graph: &mut Graph, nodes: &HashMap<(usize, usize), NodeIndex>) {
-        const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-
-        for y in 0..constants::DIM {
-            for x in 0..constants::DIM {
+    fn add_edges(
+        _grid: &Grid,
+        graph: &mut Graph,
+        nodes: &HashMap<(usize, usize), NodeIndex>,
+        dim: usize,
+    ) {
+        for y in 0..dim {
+            for x in 0..dim {
                 let current_node = nodes[&(x, y)];
                 if graph[current_node] == '#' {
                     continue;
                 }
 
-                for (dx, dy) in DIRECTIONS {
-                    if let Some((nx, ny)) = get_neighbor_coords(x, y, dx, dy) {
-                        let neighbor_node = nodes[&(nx, ny)];
-                        if graph[neighbor_node] == '.' {
-                            graph.add_edge(current_node, neighbor_node, ());
-                        }
+                for next in Pos::new(x, y).neighbors4() {
+                    if !next.in_bounds(dim, dim) {
+                        continue;
+                    }
+                    let neighbor_node = nodes[&(next.x, next.y)];
+                    if graph[neighbor_node] == '.' {
+                        graph.add_edge(current_node, neighbor_node, ());
                     }
                 }
             }
         }
     }
 
-    fn get_neighbor_coords(x: usize, y: usize, dx: i32, dy: i32) -> Option<(usize, usize)> {
-        let nx = x as i32 + dx;
-        let ny = y as i32 + dy;
-
-        if nx >= 0 && ny >= 0 && nx < constants::DIM as i32 && ny < constants::DIM as i32 {
-            Some((nx as usize, ny as usize))
-        } else {
-            None
-        }
-    }
-
-    pub fn get_node_index(graph: &Graph, Position(x, y): Position) -> miette::Result<NodeIndex> {
-        if x >= constants::DIM || y >= constants::DIM {
+    pub fn get_node_index(
+        graph: &Graph,
+        Pos { x, y }: Pos,
+        dim: usize,
+    ) -> miette::Result<NodeIndex> {
+        if x >= dim || y >= dim {
             return Err(miette!("Position ({}, {}) out of bounds", x, y));
         }
 
-        let idx = y * constants::DIM + x;
+        let idx = y * dim + x;
         graph
             .node_indices()
             .nth(idx)
@@ -154,7 +220,7 @@ mod graph {
 mod parser {
     use super::*;
 
-    pub fn parse(input: &str) -> miette::Result<Vec<Position>> {
+    pub fn parse(input: &str) -> miette::Result<Vec<Pos>> {
         input
             .lines()
             .map(|line| {
@@ -171,7 +237,7 @@ mod parser {
                     .trim()
                     .parse()
                     .map_err(|e| miette!("Failed to parse y coordinate: {}", e))?;
-                Ok(Position(x, y))
+                Ok(Pos::new(x, y))
             })
             .collect()
     }
@@ -181,6 +247,10 @@ mod parser {
 mod tests {
     use super::*;
 
+    const DIM: usize = Params::EXAMPLE.dim;
+    const BYTES: usize = Params::EXAMPLE.bytes;
+    const END: Pos = Params::EXAMPLE.end();
+
     const INPUT: &str = "\
 5,4
 4,2
@@ -210,13 +280,76 @@ mod tests {
 
     #[test]
     fn test_process() -> miette::Result<()> {
-        assert_eq!("22", process(INPUT)?);
+        assert_eq!("22", process_with_params(INPUT, Params::EXAMPLE)?);
+        // The example bytes leave the bigger puzzle space wide open
+        assert_eq!("146", process(INPUT)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_matches_dijkstra() -> miette::Result<()> {
+        let coords = parser::parse(INPUT)?;
+        let walls: HashSet<Pos> = coords.iter().take(BYTES).copied().collect();
+
+        assert_eq!(Some(22), bfs_distance(&walls, DIM, START, END));
+        assert_eq!(22, dijkstra_distance(&coords, Params::EXAMPLE)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bfs_distance_out_of_bounds() {
+        let walls = HashSet::new();
+        assert_eq!(Some(12), bfs_distance(&walls, DIM, START, END));
+        assert_eq!(None, bfs_distance(&walls, DIM, Pos::new(DIM, 0), END));
+        assert_eq!(None, bfs_distance(&walls, DIM, Pos::new(0, 100), END));
+        assert_eq!(None, bfs_distance(&walls, DIM, START, Pos::new(DIM, DIM)));
+    }
+
+    #[test]
+    fn test_reachable_count() -> miette::Result<()> {
+        // With every byte down the exit is cut off, leaving the 13 cells from START
+        // down the left edge and round to the top right corner
+        // .###...
+        // .##..##
+        // .#..#..
+        // ...#..#
+        let params = Params {
+            bytes: 25,
+            ..Params::EXAMPLE
+        };
+        assert_eq!(
+            "No path found to end position",
+            process_with_params(INPUT, params).unwrap_err().to_string()
+        );
+        assert_eq!(13, reachable_count(INPUT, params)?);
+
+        // Before any byte falls the whole space is open
+        let params = Params {
+            bytes: 0,
+            ..Params::EXAMPLE
+        };
+        assert_eq!(DIM * DIM, reachable_count(INPUT, params)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_path() -> miette::Result<()> {
+        let coords = parser::parse(INPUT)?;
+        let (distance, path) = shortest_path(&coords[..BYTES], DIM, START, END)
+            .expect("the example has a way through");
+
+        assert_eq!(22, distance);
+        assert_eq!(distance + 1, path.len());
+        assert!(path.iter().all(|pos| !coords[..BYTES].contains(pos)));
+
+        // Every byte that falls closes it off
+        assert_eq!(None, shortest_path(&coords, DIM, START, END));
         Ok(())
     }
 
     #[test]
     fn test_parser() -> miette::Result<()> {
-        assert_eq!(vec![Position(5, 4)], parser::parse("5,4")?);
+        assert_eq!(vec![Pos::new(5, 4)], parser::parse("5,4")?);
         Ok(())
     }
 
@@ -225,8 +358,8 @@ mod tests {
 
         #[test]
         fn test_graph_creation() -> miette::Result<()> {
-            let coords = vec![Position(1, 1), Position(2, 2)];
-            let graph = graph::create_graph(&coords)?;
+            let coords = vec![Pos::new(1, 1), Pos::new(2, 2)];
+            let graph = graph::create_graph(&coords, Params::EXAMPLE)?;
             assert!(graph.node_count() > 0);
             Ok(())
         }
@@ -235,15 +368,15 @@ mod tests {
         fn test_path_finding() -> miette::Result<()> {
             // Test path with known obstacles
             let coords = vec![
-                Position(1, 0),
-                Position(1, 1),
-                Position(2, 1),
-                Position(2, 2),
+                Pos::new(1, 0),
+                Pos::new(1, 1),
+                Pos::new(2, 1),
+                Pos::new(2, 2),
             ];
 
-            let graph = graph::create_graph(&coords)?;
-            let start_idx = graph::get_node_index(&graph, Position(0, 0))?;
-            let end_idx = graph::get_node_index(&graph, Position(3, 3))?;
+            let graph = graph::create_graph(&coords, Params::EXAMPLE)?;
+            let start_idx = graph::get_node_index(&graph, Pos::new(0, 0), DIM)?;
+            let end_idx = graph::get_node_index(&graph, Pos::new(3, 3), DIM)?;
 
             let paths = dijkstra(&graph, start_idx, Some(end_idx), |_| 1);
             let distance = paths.get(&end_idx).expect("Should find path");
@@ -255,18 +388,16 @@ mod tests {
         #[test]
         fn test_bounds() -> miette::Result<()> {
             let coords = vec![
-                Position(constants::DIM - 2, constants::DIM - 2),
-                Position(0, constants::DIM - 1),
-                Position(constants::DIM - 1, 0),
+                Pos::new(DIM - 2, DIM - 2),
+                Pos::new(0, DIM - 1),
+                Pos::new(DIM - 1, 0),
             ];
 
-            let graph = graph::create_graph(&coords)?;
+            let graph = graph::create_graph(&coords, Params::EXAMPLE)?;
 
-            assert!(graph::get_node_index(&graph, START).is_ok());
-            assert!(graph::get_node_index(&graph, END).is_ok());
-            assert!(
-                graph::get_node_index(&graph, Position(constants::DIM, constants::DIM)).is_err()
-            );
+            assert!(graph::get_node_index(&graph, START, DIM).is_ok());
+            assert!(graph::get_node_index(&graph, END, DIM).is_ok());
+            assert!(graph::get_node_index(&graph, Pos::new(DIM, DIM), DIM).is_err());
 
             Ok(())
         }
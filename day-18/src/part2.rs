@@ -1,4 +1,7 @@
-use graph::{add_wall_to_graph, build_initial_graph, node_to_position, would_block_all_paths};
+use graph::{
+    add_wall_to_graph, build_initial_graph, node_to_position, render, would_block_all_paths,
+};
+use grid::Pos;
 use miette::miette;
 use petgraph::{
     algo::astar,
@@ -6,45 +9,92 @@ use petgraph::{
 };
 use std::collections::HashMap;
 
-#[cfg(test)]
-mod constants {
-    pub const DIM: usize = 7;
-    pub const INITIAL_BYTES: usize = 12;
-}
+use crate::params::Params;
 
-#[cfg(not(test))]
-mod constants {
-    pub const DIM: usize = 71;
-    pub const INITIAL_BYTES: usize = 1024;
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub struct Position(pub usize, pub usize);
-
-pub const START: Position = Position(0, 0);
-pub const END: Position = Position(constants::DIM - 1, constants::DIM - 1);
+pub const START: Pos = Pos::new(0, 0);
 
 type Grid = Vec<Vec<char>>;
 type Graph = DiGraph<char, ()>;
+type NodeMap = HashMap<(usize, usize), NodeIndex>;
 
+/// Solves on the real puzzle's memory space, see [`process_with_params`] to pick another
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    process_with_params(input, Params::PUZZLE)
+}
+
+/// The first byte to cut the exit off in a `params.dim` square, checking from
+/// `params.bytes` onwards
+#[tracing::instrument]
+pub fn process_with_params(input: &str, params: Params) -> miette::Result<String> {
+    let (x, y) = process_typed(input, params)?;
+    Ok(format!("{},{}", x, y))
+}
+
+/// Like [`process_with_params`], giving the blocking byte as an `(x, y)` pair
+pub fn process_typed(input: &str, params: Params) -> miette::Result<(usize, usize)> {
+    let input: &str = &grid::normalize(input);
     let coords = parser::parse(input)?;
-    let blocking_coord = find_blocking_coordinate_optimized(&coords)?;
-    Ok(format!("{},{}", blocking_coord.0, blocking_coord.1))
+    let Pos { x, y } = find_blocking_coordinate_optimized(&coords, params)?;
+    Ok((x, y))
 }
 
-fn find_blocking_coordinate_optimized(coords: &[Position]) -> miette::Result<Position> {
-    let mut bytes = constants::INITIAL_BYTES;
-    let initial_coords: Vec<Position> = coords.iter().take(bytes).copied().collect();
+/// Both answers from a single parse on the real puzzle's memory space, see
+/// [`solve_with_params`] to pick another
+pub fn solve(input: &str) -> miette::Result<(usize, String)> {
+    solve_with_params(input, Params::PUZZLE)
+}
+
+/// Steps to the exit once `params.bytes` bytes have fallen, and the first byte to
+/// cut it off. The graph is built once: the distance is read off it before the
+/// blocking search starts adding walls.
+pub fn solve_with_params(input: &str, params: Params) -> miette::Result<(usize, String)> {
+    let input: &str = &grid::normalize(input);
+    let coords = parser::parse(input)?;
+    crate::part1::validate_coordinates(&coords, params.dim)?;
+
+    let fallen = &coords[..params.bytes.min(coords.len())];
+    let (graph, node_map) = build_initial_graph(fallen, params.dim)?;
+
+    let start_idx = graph::get_node_index(&graph, START, params.dim)?;
+    let end_idx = graph::get_node_index(&graph, params.end(), params.dim)?;
+    let (distance, _) = astar(
+        &graph,
+        start_idx,
+        |n| n == end_idx,
+        |_| 1,
+        |n| node_to_position(&graph, n, params.dim).manhattan(params.end()),
+    )
+    .ok_or_else(|| miette!("No path found to end position"))?;
+
+    let Pos { x, y } = search_blocking_coordinate(coords.as_slice(), params, graph, &node_map)?;
+    Ok((distance, format!("{},{}", x, y)))
+}
+
+fn find_blocking_coordinate_optimized(coords: &[Pos], params: Params) -> miette::Result<Pos> {
+    crate::part1::validate_coordinates(coords, params.dim)?;
+    let initial_coords: Vec<Pos> = coords.iter().take(params.bytes).copied().collect();
 
     // Build initial graph
-    let (mut graph, node_map) = build_initial_graph(&initial_coords)?;
+    let (graph, node_map) = build_initial_graph(&initial_coords, params.dim)?;
+    search_blocking_coordinate(coords, params, graph, &node_map)
+}
+
+/// Drops the bytes after the first `params.bytes` into `graph`, which already holds
+/// those, until one cuts the exit off
+fn search_blocking_coordinate(
+    coords: &[Pos],
+    params: Params,
+    mut graph: Graph,
+    node_map: &NodeMap,
+) -> miette::Result<Pos> {
+    let Params { dim, mut bytes } = params;
+    let end = params.end();
     let mut last_valid = true;
 
     // Get indices once
-    let start_idx = graph::get_node_index(&graph, START)?;
-    let end_idx = graph::get_node_index(&graph, END)?;
+    let start_idx = graph::get_node_index(&graph, START, dim)?;
+    let end_idx = graph::get_node_index(&graph, end, dim)?;
 
     loop {
         if bytes >= coords.len() {
@@ -56,12 +106,12 @@ fn find_blocking_coordinate_optimized(coords: &[Position]) -> miette::Result<Pos
         let next_coord = coords[bytes];
 
         // Quick check if this wall would block all possible paths
-        if would_block_all_paths(&graph, &node_map, next_coord, start_idx, end_idx)? {
+        if would_block_all_paths(&graph, node_map, next_coord, start_idx, end_idx, dim)? {
             return Ok(next_coord);
         }
 
         // Add wall and update edges
-        add_wall_to_graph(&mut graph, &node_map, next_coord)?;
+        add_wall_to_graph(&mut graph, node_map, next_coord)?;
 
         // Use A* instead of Dijkstra for potentially faster pathfinding
         let path_exists = astar(
@@ -69,11 +119,7 @@ fn find_blocking_coordinate_optimized(coords: &[Position]) -> miette::Result<Pos
             start_idx,
             |n| n == end_idx,
             |_| 1,
-            |n| {
-                let Position(x, y) = node_to_position(&graph, n);
-                let Position(end_x, end_y) = END;
-                ((x as i32 - end_x as i32).abs() + (y as i32 - end_y as i32).abs()) as u32
-            },
+            |n| node_to_position(&graph, n, dim).manhattan(end) as u32,
         )
         .is_some();
 
@@ -91,9 +137,27 @@ fn find_blocking_coordinate_optimized(coords: &[Position]) -> miette::Result<Pos
     Err(miette!("No blocking coordinate found"))
 }
 
-// fn find_blocking_coordinate(coords: &[Position]) -> miette::Result<Position> {
+/// The `dim` x `dim` memory space drawn after each of `coords` falls, starting from
+/// the empty space, so there is one more frame than there are bytes. Walls are added
+/// to one graph as they land rather than rebuilding it for every frame. Errors up
+/// front if any byte lands outside the space.
+pub fn fall_frames(
+    coords: &[Pos],
+    dim: usize,
+) -> miette::Result<impl Iterator<Item = String> + '_> {
+    crate::part1::validate_coordinates(coords, dim)?;
+    let (mut graph, node_map) = build_initial_graph(&[], dim)?;
+    let empty = render(&graph, dim);
+
+    Ok(std::iter::once(empty).chain(coords.iter().map(move |&pos| {
+        add_wall_to_graph(&mut graph, &node_map, pos).expect("coordinates were validated");
+        render(&graph, dim)
+    })))
+}
+
+// fn find_blocking_coordinate(coords: &[Pos]) -> miette::Result<Pos> {
 //     let mut bytes = constants::INITIAL_BYTES;
-//     let mut previous_coords: Vec<Position> = coords.iter().take(bytes).copied().collect();
+//     let mut previous_coords: Vec<Pos> = coords.iter().take(bytes).copied().collect();
 
 //     loop {
 //         if bytes >= coords.len() {
@@ -123,13 +187,11 @@ mod graph {
 
     use super::*;
 
-    pub fn build_initial_graph(
-        coords: &[Position],
-    ) -> miette::Result<(Graph, HashMap<(usize, usize), NodeIndex>)> {
-        let mut grid = vec![vec!['.'; constants::DIM]; constants::DIM];
+    pub fn build_initial_graph(coords: &[Pos], dim: usize) -> miette::Result<(Graph, NodeMap)> {
+        let mut grid = vec![vec!['.'; dim]; dim];
 
         // Place initial walls
-        for &Position(x, y) in coords {
+        for &Pos { x, y } in coords {
             grid[y][x] = '#';
         }
 
@@ -137,26 +199,32 @@ mod graph {
         let mut node_map = HashMap::new();
 
         // Create nodes
-        for y in 0..constants::DIM {
-            for x in 0..constants::DIM {
-                let node = graph.add_node(grid[y][x]);
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                let node = graph.add_node(cell);
                 node_map.insert((x, y), node);
             }
         }
 
         // Add initial edges
-        add_all_edges(&mut graph, &grid, &node_map);
+        add_all_edges(&mut graph, &grid, &node_map, dim);
 
         Ok((graph, node_map))
     }
 
+    fn node_at(node_map: &NodeMap, Pos { x, y }: Pos) -> miette::Result<NodeIndex> {
+        node_map
+            .get(&(x, y))
+            .copied()
+            .ok_or_else(|| miette!("Coordinates ({}, {}) out of bounds", x, y))
+    }
+
     pub fn add_wall_to_graph(
         graph: &mut Graph,
-        node_map: &HashMap<(usize, usize), NodeIndex>,
-        pos: Position,
+        node_map: &NodeMap,
+        pos: Pos,
     ) -> miette::Result<()> {
-        let Position(x, y) = pos;
-        let node = node_map[&(x, y)];
+        let node = node_at(node_map, pos)?;
 
         // Update node value
         graph[node] = '#';
@@ -175,12 +243,14 @@ mod graph {
 
     pub fn would_block_all_paths(
         graph: &Graph,
-        node_map: &HashMap<(usize, usize), NodeIndex>,
-        pos: Position,
+        node_map: &NodeMap,
+        pos: Pos,
         start_idx: NodeIndex,
         end_idx: NodeIndex,
+        dim: usize,
     ) -> miette::Result<bool> {
-        let Position(x, y) = pos;
+        let node = node_at(node_map, pos)?;
+        let end = node_to_position(graph, end_idx, dim);
 
         // If the wall would block the only remaining path
         let current_paths = astar(
@@ -188,86 +258,77 @@ mod graph {
             start_idx,
             |n| n == end_idx,
             |_| 1,
-            |n| {
-                let Position(px, py) = node_to_position(graph, n);
-                let Position(end_x, end_y) = END;
-                ((px as i32 - end_x as i32).abs() + (py as i32 - end_y as i32).abs()) as u32
-            },
+            |n| node_to_position(graph, n, dim).manhattan(end) as u32,
         );
 
         if let Some((_, path)) = current_paths {
             // Check if the new wall would block this path
-            if path.iter().any(|&n| node_map[&(x, y)] == n) {
+            if path.contains(&node) {
                 // Check if there are alternative paths
                 let mut temp_graph = graph.clone();
                 add_wall_to_graph(&mut temp_graph, node_map, pos)?;
 
-                return Ok(!astar(
+                return Ok(astar(
                     &temp_graph,
                     start_idx,
                     |n| n == end_idx,
                     |_| 1,
-                    |n| {
-                        let Position(px, py) = node_to_position(&temp_graph, n);
-                        let Position(end_x, end_y) = END;
-                        ((px as i32 - end_x as i32).abs() + (py as i32 - end_y as i32).abs()) as u32
-                    },
+                    |n| node_to_position(&temp_graph, n, dim).manhattan(end) as u32,
                 )
-                .is_some());
+                .is_none());
             }
         }
 
         Ok(false)
     }
 
-    pub fn node_to_position(_graph: &Graph, node: NodeIndex) -> Position {
+    pub fn node_to_position(_graph: &Graph, node: NodeIndex, dim: usize) -> Pos {
         let idx = node.index();
-        Position(idx % constants::DIM, idx / constants::DIM)
+        Pos::new(idx % dim, idx / dim)
     }
 
-    fn add_all_edges(
-        graph: &mut Graph,
-        grid: &Grid,
-        node_map: &HashMap<(usize, usize), NodeIndex>,
-    ) {
-        const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+    /// One line per row, `#` for a fallen byte and `.` for open space
+    pub fn render(graph: &Graph, dim: usize) -> String {
+        graph
+            .node_weights()
+            .collect::<Vec<_>>()
+            .chunks(dim)
+            .map(|row| row.iter().copied().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-        for y in 0..constants::DIM {
-            for x in 0..constants::DIM {
+    fn add_all_edges(graph: &mut Graph, grid: &Grid, node_map: &NodeMap, dim: usize) {
+        for y in 0..dim {
+            for x in 0..dim {
                 let current_node = node_map[&(x, y)];
                 if grid[y][x] == '#' {
                     continue;
                 }
 
-                for (dx, dy) in DIRECTIONS {
-                    if let Some((nx, ny)) = get_neighbor_coords(x, y, dx, dy) {
-                        let neighbor_node = node_map[&(nx, ny)];
-                        if grid[ny][nx] == '.' {
-                            graph.add_edge(current_node, neighbor_node, ());
-                        }
+                for next in Pos::new(x, y).neighbors4() {
+                    if !next.in_bounds(dim, dim) {
+                        continue;
+                    }
+                    let neighbor_node = node_map[&(next.x, next.y)];
+                    if grid[next.y][next.x] == '.' {
+                        graph.add_edge(current_node, neighbor_node, ());
                     }
                 }
             }
         }
     }
 
-    fn get_neighbor_coords(x: usize, y: usize, dx: i32, dy: i32) -> Option<(usize, usize)> {
-        let nx = x as i32 + dx;
-        let ny = y as i32 + dy;
-
-        if nx >= 0 && ny >= 0 && nx < constants::DIM as i32 && ny < constants::DIM as i32 {
-            Some((nx as usize, ny as usize))
-        } else {
-            None
-        }
-    }
-
-    pub fn get_node_index(graph: &Graph, Position(x, y): Position) -> miette::Result<NodeIndex> {
-        if x >= constants::DIM || y >= constants::DIM {
+    pub fn get_node_index(
+        graph: &Graph,
+        Pos { x, y }: Pos,
+        dim: usize,
+    ) -> miette::Result<NodeIndex> {
+        if x >= dim || y >= dim {
             return Err(miette!("Position ({}, {}) out of bounds", x, y));
         }
 
-        let idx = y * constants::DIM + x;
+        let idx = y * dim + x;
         graph
             .node_indices()
             .nth(idx)
@@ -286,7 +347,7 @@ mod graph {
 mod parser {
     use super::*;
 
-    pub fn parse(input: &str) -> miette::Result<Vec<Position>> {
+    pub fn parse(input: &str) -> miette::Result<Vec<Pos>> {
         input
             .lines()
             .map(|line| {
@@ -303,7 +364,7 @@ mod parser {
                     .trim()
                     .parse()
                     .map_err(|e| miette!("Failed to parse y coordinate: {}", e))?;
-                Ok(Position(x, y))
+                Ok(Pos::new(x, y))
             })
             .collect()
     }
@@ -313,12 +374,134 @@ mod parser {
 mod tests {
     use super::*;
 
+    const INPUT: &str = "\
+5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0";
+
+    #[test]
+    fn test_process() -> miette::Result<()> {
+        assert_eq!("6,1", process_with_params(INPUT, Params::EXAMPLE)?);
+        // Nothing in the example can wall off the bigger puzzle space
+        assert!(process(INPUT).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve() -> miette::Result<()> {
+        assert_eq!(
+            (22, "6,1".to_string()),
+            solve_with_params(INPUT, Params::EXAMPLE)?
+        );
+
+        // Same answers as running the parts separately
+        let (distance, blocking) = solve_with_params(INPUT, Params::EXAMPLE)?;
+        assert_eq!(
+            crate::part1::process_with_params(INPUT, Params::EXAMPLE)?,
+            distance.to_string()
+        );
+        assert_eq!(process_with_params(INPUT, Params::EXAMPLE)?, blocking);
+
+        assert!(solve_with_params("7,0", Params::EXAMPLE).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fall_frames() -> miette::Result<()> {
+        let coords = parser::parse(INPUT)?;
+        let frames = fall_frames(&coords, Params::EXAMPLE.dim)?.collect::<Vec<_>>();
+
+        assert_eq!(coords.len() + 1, frames.len());
+        assert!(!frames[0].contains('#'));
+        assert_eq!(
+            "\
+...#...
+..#..#.
+....#..
+...#..#
+..#..#.
+.#..#..
+#.#....",
+            frames[Params::EXAMPLE.bytes]
+        );
+        assert_eq!(coords.len(), frames.last().unwrap().matches('#').count());
+
+        // A byte outside the space is an error before any frame is drawn
+        let coords = parser::parse("1,1\n7,0\n2,2")?;
+        let err = fall_frames(&coords, Params::EXAMPLE.dim).err().unwrap();
+        assert_eq!("Coordinates (7, 0) out of bounds (max: 6)", err.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_typed() -> miette::Result<()> {
+        let (x, y) = process_typed(INPUT, Params::EXAMPLE)?;
+        assert_eq!((6, 1), (x, y));
+        assert_eq!(
+            format!("{},{}", x, y),
+            process_with_params(INPUT, Params::EXAMPLE)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let input = "100,100\n0,0";
+        let expected = "Coordinates (100, 100) out of bounds (max: 6)";
+
+        let err = process_typed(input, Params::EXAMPLE).unwrap_err();
+        assert_eq!(expected, err.to_string());
+        let err = process_with_params(input, Params::EXAMPLE).unwrap_err();
+        assert_eq!(expected, err.to_string());
+        let err = solve_with_params(input, Params::EXAMPLE).unwrap_err();
+        assert_eq!(expected, err.to_string());
+        let err = crate::part1::process_with_params(input, Params::EXAMPLE).unwrap_err();
+        assert_eq!(expected, err.to_string());
+    }
+
     mod graph_tests {
         use super::*;
 
+        #[test]
+        fn test_node_positions() -> miette::Result<()> {
+            let coords = parser::parse(INPUT)?;
+            let Params { dim, bytes } = Params::EXAMPLE;
+            let (graph, _) = build_initial_graph(&coords[..bytes], dim)?;
+
+            let end_idx = graph::get_node_index(&graph, Params::EXAMPLE.end(), dim)?;
+            assert_eq!(
+                Params::EXAMPLE.end(),
+                node_to_position(&graph, end_idx, dim)
+            );
+            assert!(graph::get_node_index(&graph, Pos::new(dim, 0), dim).is_err());
+            Ok(())
+        }
+
         // #[test]
         // fn test_graph_creation() -> miette::Result<()> {
-        //     let coords = vec![Position(1, 1), Position(2, 2)];
+        //     let coords = vec![Pos::new(1, 1), Pos::new(2, 2)];
         //     let graph = graph::create_graph(&coords)?;
         //     assert!(graph.node_count() > 0);
         //     Ok(())
@@ -327,15 +510,15 @@ mod tests {
         // #[test]
         // fn test_path_finding() -> miette::Result<()> {
         //     let coords = vec![
-        //         Position(1, 0),
-        //         Position(1, 1),
-        //         Position(2, 1),
-        //         Position(2, 2),
+        //         Pos::new(1, 0),
+        //         Pos::new(1, 1),
+        //         Pos::new(2, 1),
+        //         Pos::new(2, 2),
         //     ];
 
         //     let graph = graph::create_graph(&coords)?;
-        //     let start_idx = graph::get_node_index(&graph, Position(0, 0))?;
-        //     let end_idx = graph::get_node_index(&graph, Position(3, 3))?;
+        //     let start_idx = graph::get_node_index(&graph, Pos::new(0, 0))?;
+        //     let end_idx = graph::get_node_index(&graph, Pos::new(3, 3))?;
 
         //     let paths = astar(&graph, start_idx, Some(end_idx), |_| 1);
         //     let distance = paths.get(&end_idx).expect("Should find path");
@@ -347,16 +530,16 @@ mod tests {
         // #[test]
         // fn test_bounds() -> miette::Result<()> {
         //     let coords = vec![
-        //         Position(constants::DIM - 2, constants::DIM - 2),
-        //         Position(0, constants::DIM - 1),
-        //         Position(constants::DIM - 1, 0),
+        //         Pos::new(constants::DIM - 2, constants::DIM - 2),
+        //         Pos::new(0, constants::DIM - 1),
+        //         Pos::new(constants::DIM - 1, 0),
         //     ];
 
         //     let graph = graph::create_graph(&coords)?;
 
         //     assert!(graph::get_node_index(&graph, START).is_ok());
         //     assert!(graph::get_node_index(&graph, END).is_ok());
-        //     assert!(graph::get_node_index(&graph, Position(constants::DIM, constants::DIM)).is_err());
+        //     assert!(graph::get_node_index(&graph, Pos::new(constants::DIM, constants::DIM)).is_err());
 
         //     Ok(())
         // }
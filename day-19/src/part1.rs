@@ -2,6 +2,7 @@ use std::collections::HashSet;
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let (_, (patterns, designs)) =
         parser::parse(input).map_err(|e| miette::miette!("Failed to parse input: {}", e))?;
 
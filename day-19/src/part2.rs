@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 #[cfg(not(feature = "debug"))]
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let (_, (patterns, designs)) =
         parser::parse(input).map_err(|e| miette::miette!("Failed to parse input: {}", e))?;
 
@@ -57,6 +58,7 @@ fn find_combinations<'a>(
 #[cfg(feature = "debug")]
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let (_, (patterns, designs)) =
         parser::parse(input).map_err(|e| miette::miette!("Failed to parse input: {}", e))?;
 
@@ -1,23 +1,49 @@
 use pathfinding::grid::Grid as PathGrid;
 use pathfinding::prelude::*;
 use rayon::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 mod types {
     pub type Position = (usize, usize);
 }
 use types::Position;
 
-// Configuration constants
-#[cfg(test)]
-pub const SHORTCUT_THRESHOLD: usize = 30;
-
-#[cfg(not(test))]
+/// Fewest picoseconds a cheat has to save to count in the real puzzle
 pub const SHORTCUT_THRESHOLD: usize = 100;
 
 // Main processing function
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    process_with_threshold(input, SHORTCUT_THRESHOLD)
+}
+
+/// Counts the cheats saving at least `threshold` picoseconds, so the worked examples
+/// can use their smaller savings
+#[tracing::instrument]
+pub fn process_with_threshold(input: &str, threshold: usize) -> miette::Result<String> {
+    Ok(savings(input, threshold)?.len().to_string())
+}
+
+/// Number of cheats for each picosecond saving, smallest saving first, which reads
+/// just like the puzzle's table for the examples
+pub fn count_by_savings(input: &str) -> miette::Result<BTreeMap<usize, usize>> {
+    Ok(savings(input, 1)?
+        .into_values()
+        .fold(BTreeMap::new(), |mut counts, saving| {
+            *counts.entry(saving).or_default() += 1;
+            counts
+        }))
+}
+
+/// Cheats in a [`count_by_savings`] table saving at least `threshold` picoseconds
+pub fn total_at_least(counts: &BTreeMap<usize, usize>, threshold: usize) -> usize {
+    counts.range(threshold..).map(|(_, count)| count).sum()
+}
+
+/// Picoseconds saved by each cheat that saves at least `threshold`, keyed by the
+/// wall it goes through
+fn savings(input: &str, threshold: usize) -> miette::Result<HashMap<Position, usize>> {
+    let input: &str = &grid::normalize(input);
     // Parse input and create initial grid
     let parsed_grid = parser::parse_input(input)?;
     let grid = graph::create_grid(&parsed_grid)?;
@@ -29,59 +55,41 @@ pub fn process(input: &str) -> miette::Result<String> {
 
     // Find and evaluate shortcut candidates
     let candidates = shortcuts::find_candidates(&path_grid)?;
-    let improvements =
-        shortcuts::evaluate_candidates(&path_grid, &candidates, start, end, original_path_length)?;
-
-    // Count significant shortcuts
-    let significant_shortcuts = improvements
-        .iter()
-        .filter(|(_, &improvement)| improvement >= SHORTCUT_THRESHOLD)
-        .count();
-
-    Ok(significant_shortcuts.to_string())
+    shortcuts::evaluate_candidates(
+        &path_grid,
+        &candidates,
+        start,
+        end,
+        original_path_length,
+        threshold,
+    )
 }
 
 // Parser module - Handles input parsing
 mod parser {
-    use nom::{
-        character::complete::{newline, satisfy},
-        multi::{many1, separated_list1},
-        IResult, Parser,
-    };
-    use nom_locate::LocatedSpan;
-
-    pub type Span<'a> = LocatedSpan<&'a str>;
-
     const WALL: char = '#';
     const EMPTY: char = '.';
     pub(crate) const START: char = 'S';
     pub(crate) const END: char = 'E';
 
     #[derive(Debug, Clone, PartialEq)]
-    pub struct Cell<'a> {
+    pub struct Cell {
         pub value: char,
-        pub position: Span<'a>,
+        pub position: (usize, usize),
     }
 
-    pub type ParsedGrid<'a> = Vec<Vec<Cell<'a>>>;
+    pub type ParsedGrid = Vec<Vec<Cell>>;
 
     pub fn parse_input(input: &str) -> miette::Result<ParsedGrid> {
-        let span = Span::new(input);
-        let (_, grid) = parse(span).map_err(|e| miette::miette!("Failed to parse input: {}", e))?;
-        Ok(grid)
-    }
-
-    pub(crate) fn parse(input: Span) -> IResult<Span, ParsedGrid> {
-        separated_list1(
-            newline,
-            many1(
-                satisfy(|c| matches!(c, WALL | EMPTY | START | END)).map(|c| Cell {
-                    value: c,
-                    position: input,
-                }),
-            ),
-        )
-        .parse(input)
+        let grid = grid::parse_char_grid(input, &[WALL, EMPTY, START, END])?;
+        Ok(grid
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|(value, position)| Cell { value, position })
+                    .collect()
+            })
+            .collect())
     }
 }
 
@@ -97,19 +105,32 @@ mod graph {
     pub fn find_endpoints(
         parsed_grid: &parser::ParsedGrid,
     ) -> miette::Result<(Position, Position)> {
-        let start = find_cells(parsed_grid, |cell| cell.value == 'S')
-            .into_iter()
-            .next()
-            .ok_or(miette::miette!("Start position not found"))?;
-
-        let end = find_cells(parsed_grid, |cell| cell.value == 'E')
-            .into_iter()
-            .next()
-            .ok_or(miette::miette!("End position not found"))?;
+        let start = find_unique_cell(parsed_grid, parser::START, "start")?;
+        let end = find_unique_cell(parsed_grid, parser::END, "end")?;
 
         Ok((start, end))
     }
 
+    /// The one cell holding `marker`. A missing or repeated marker is an error, as
+    /// picking one of several would quietly change the race.
+    fn find_unique_cell(
+        parsed_grid: &parser::ParsedGrid,
+        marker: char,
+        name: &str,
+    ) -> miette::Result<Position> {
+        match find_cells(parsed_grid, |cell| cell.value == marker)[..] {
+            [position] => Ok(position),
+            [] => Err(miette::miette!("No {} position ({}) found", name, marker)),
+            ref positions => Err(miette::miette!(
+                "Expected one {} position ({}) but found {} at {:?}",
+                name,
+                marker,
+                positions.len(),
+                positions
+            )),
+        }
+    }
+
     pub fn create_pathfinding_grid(grid: &PathGrid) -> PathGrid {
         let mut pathfinding_grid = grid.clone();
         pathfinding_grid.invert();
@@ -152,6 +173,17 @@ mod pathing {
         Ok(path_length)
     }
 
+    /// Distance from `start` to every reachable track cell
+    pub fn distances_from(grid: &PathGrid, start: Position) -> HashMap<Position, usize> {
+        let mut distances: HashMap<_, _> =
+            dijkstra_all(&start, |p| grid.neighbours(*p).into_iter().map(|n| (n, 1)))
+                .into_iter()
+                .map(|(pos, (_, cost))| (pos, cost))
+                .collect();
+        distances.insert(start, 0);
+        distances
+    }
+
     fn manhattan_distance(pos: Position, target: Position) -> usize {
         ((pos.0 as i32 - target.0 as i32).abs() + (pos.1 as i32 - target.1 as i32).abs()) as usize
     }
@@ -167,7 +199,38 @@ mod shortcuts {
         start: Position,
         end: Position,
         original_length: usize,
+        threshold: usize,
     ) -> miette::Result<HashMap<Position, usize>> {
+        evaluate_candidates_with(
+            grid,
+            candidates,
+            start,
+            end,
+            original_length,
+            threshold,
+            true,
+        )
+    }
+
+    /// Like [`evaluate_candidates`], but with `prune` unset every candidate gets its
+    /// exact path length recomputed, even those that can't reach `threshold`
+    pub fn evaluate_candidates_with(
+        grid: &PathGrid,
+        candidates: &HashSet<Position>,
+        start: Position,
+        end: Position,
+        original_length: usize,
+        threshold: usize,
+        prune: bool,
+    ) -> miette::Result<HashMap<Position, usize>> {
+        let distances = pathing::distances_from(grid, start);
+        let candidates: HashSet<_> = candidates
+            .iter()
+            .copied()
+            .filter(|&pos| !prune || saving_upper_bound(grid, &distances, pos) >= threshold)
+            .collect();
+        let candidates = &candidates;
+
         // Process candidates in chunks to reduce lock contention
         const CHUNK_SIZE: usize = 32;
 
@@ -182,7 +245,7 @@ mod shortcuts {
                     test_grid.add_vertex(pos);
                     if let Ok(new_length) = pathing::find_shortest_path(&test_grid, start, end) {
                         let improvement = original_length - new_length;
-                        if improvement >= SHORTCUT_THRESHOLD {
+                        if improvement >= threshold {
                             local_results.insert(pos, improvement);
                         }
                     }
@@ -196,6 +259,34 @@ mod shortcuts {
         Ok(results)
     }
 
+    /// Most a cheat through `pos` could save: stepping in from the track cell `a`
+    /// and out onto `b` costs 2 picoseconds, so it saves at most
+    /// `distance[b] - distance[a] - 2`, whatever the rest of the maze looks like
+    pub fn saving_upper_bound(
+        grid: &PathGrid,
+        distances: &HashMap<Position, usize>,
+        pos: Position,
+    ) -> usize {
+        let (x, y) = pos;
+        let neighbours = [
+            x.checked_sub(1).map(|x| (x, y)),
+            Some((x + 1, y)),
+            y.checked_sub(1).map(|y| (x, y)),
+            Some((x, y + 1)),
+        ];
+        let reached: Vec<_> = neighbours
+            .into_iter()
+            .flatten()
+            .filter(|&n| grid.has_vertex(n))
+            .filter_map(|n| distances.get(&n).copied())
+            .collect();
+
+        match (reached.iter().min(), reached.iter().max()) {
+            (Some(&nearest), Some(&furthest)) => furthest.saturating_sub(nearest + 2),
+            _ => 0,
+        }
+    }
+
     pub fn find_candidates(grid: &PathGrid) -> miette::Result<HashSet<Position>> {
         let mut candidates = HashSet::new();
         let width = grid.width;
@@ -248,6 +339,8 @@ mod tests {
     use super::*;
     use itertools::Itertools;
 
+    const EXAMPLE_THRESHOLD: usize = 30;
+
     const EXAMPLE_LARGE: &str = "\
 ###############
 #...#...#.....#
@@ -274,7 +367,115 @@ mod tests {
 
     #[test]
     fn test_process_large() -> miette::Result<()> {
-        assert_eq!("4", process(EXAMPLE_LARGE)?);
+        assert_eq!(
+            "4",
+            process_with_threshold(EXAMPLE_LARGE, EXAMPLE_THRESHOLD)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_by_savings() -> miette::Result<()> {
+        let counts = count_by_savings(EXAMPLE_LARGE)?;
+
+        // The puzzle's table for the large example, in the same order
+        assert_eq!(
+            vec![
+                (2, 14),
+                (4, 14),
+                (6, 2),
+                (8, 4),
+                (10, 2),
+                (12, 3),
+                (20, 1),
+                (36, 1),
+                (38, 1),
+                (40, 1),
+                (64, 1)
+            ],
+            counts.into_iter().collect::<Vec<_>>()
+        );
+
+        let counts = count_by_savings(EXAMPLE_LARGE)?;
+        assert_eq!(44, total_at_least(&counts, 0));
+        assert_eq!(4, total_at_least(&counts, EXAMPLE_THRESHOLD));
+        assert_eq!(
+            process_with_threshold(EXAMPLE_LARGE, EXAMPLE_THRESHOLD)?,
+            total_at_least(&counts, EXAMPLE_THRESHOLD).to_string()
+        );
+        assert_eq!(0, total_at_least(&counts, 65));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pruned_candidates() -> miette::Result<()> {
+        let parsed_grid = parser::parse_input(EXAMPLE_LARGE)?;
+        let grid = graph::create_grid(&parsed_grid)?;
+        let (start, end) = graph::find_endpoints(&parsed_grid)?;
+        let path_grid = graph::create_pathfinding_grid(&grid);
+        let original_length = pathing::find_shortest_path(&path_grid, start, end)?;
+        let candidates = shortcuts::find_candidates(&path_grid)?;
+
+        for threshold in [1, 2, 12, EXAMPLE_THRESHOLD, 64, 65] {
+            let evaluate = |prune| {
+                shortcuts::evaluate_candidates_with(
+                    &path_grid,
+                    &candidates,
+                    start,
+                    end,
+                    original_length,
+                    threshold,
+                    prune,
+                )
+            };
+            assert_eq!(evaluate(false)?, evaluate(true)?, "threshold {threshold}");
+        }
+
+        // The bound never undersells the exact saving
+        let distances = pathing::distances_from(&path_grid, start);
+        let exact = shortcuts::evaluate_candidates_with(
+            &path_grid,
+            &candidates,
+            start,
+            end,
+            original_length,
+            1,
+            false,
+        )?;
+        for (&pos, &saving) in &exact {
+            assert!(shortcuts::saving_upper_bound(&path_grid, &distances, pos) >= saving);
+        }
+        assert_eq!(
+            64,
+            shortcuts::saving_upper_bound(&path_grid, &distances, (6, 7))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_endpoints_validation() -> miette::Result<()> {
+        let two_ends = parser::parse_input(
+            "\
+#######
+#S#..E#
+#.#.#.#
+#...#E#
+#######",
+        )?;
+        let error = graph::find_endpoints(&two_ends).unwrap_err();
+        assert!(error.to_string().contains("found 2 at [(5, 1), (5, 3)]"));
+
+        let no_start = parser::parse_input(
+            "\
+#######
+#.#...#
+#.#.#.#
+#...#E#
+#######",
+        )?;
+        let error = graph::find_endpoints(&no_start).unwrap_err();
+        assert_eq!("No start position (S) found", error.to_string());
+        assert!(process(&EXAMPLE_SMALL.replace('S', ".")).is_err());
         Ok(())
     }
 
@@ -309,6 +510,7 @@ mod tests {
         // Verify start and end cells are in correct positions
         assert_eq!(parsed_grid[1][1].value, parser::START);
         assert_eq!(parsed_grid[3][5].value, parser::END);
+        assert_eq!(parsed_grid[3][5].position, (5, 3));
 
         Ok(())
     }
@@ -374,8 +576,14 @@ mod tests {
 
         // Find and evaluate candidates
         let candidates = shortcuts::find_candidates(&path_grid)?;
-        let improvements =
-            shortcuts::evaluate_candidates(&path_grid, &candidates, start, end, original_length)?;
+        let improvements = shortcuts::evaluate_candidates(
+            &path_grid,
+            &candidates,
+            start,
+            end,
+            original_length,
+            EXAMPLE_THRESHOLD,
+        )?;
 
         // Verify we found improvements
         assert!(!improvements.is_empty());
@@ -7,16 +7,20 @@ mod types {
 }
 use types::Position;
 
-// Configuration constants
-#[cfg(test)]
-pub const SHORTCUT_THRESHOLD: usize = 10;
-
-#[cfg(not(test))]
+/// Fewest picoseconds a cheat has to save to count in the real puzzle
 pub const SHORTCUT_THRESHOLD: usize = 100;
 
 // Main processing function
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    process_with_threshold(input, SHORTCUT_THRESHOLD)
+}
+
+/// Counts the cheats saving at least `threshold` picoseconds, so the worked examples
+/// can use their smaller savings
+#[tracing::instrument]
+pub fn process_with_threshold(input: &str, threshold: usize) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let parsed_grid = parser::parse_input(input)?;
     let grid = graph::create_grid(&parsed_grid)?;
     let (start, end) = graph::find_endpoints(&parsed_grid)?;
@@ -25,53 +29,43 @@ pub fn process(input: &str) -> miette::Result<String> {
     let original_path_length = pathing::find_shortest_path(&path_grid, start, end)?;
 
     let candidates = shortcuts::find_candidates(&path_grid)?;
-    let improvements =
-        shortcuts::evaluate_candidates(&path_grid, &candidates, start, end, original_path_length)?;
+    let improvements = shortcuts::evaluate_candidates(
+        &path_grid,
+        &candidates,
+        start,
+        end,
+        original_path_length,
+        threshold,
+    )?;
 
     Ok(improvements.len().to_string())
 }
 
 // Parser module - Handles input parsing
 mod parser {
-    use nom::{
-        character::complete::{newline, satisfy},
-        multi::{many1, separated_list1},
-        IResult, Parser,
-    };
-    use nom_locate::LocatedSpan;
-
-    pub type Span<'a> = LocatedSpan<&'a str>;
-
     const WALL: char = '#';
     const EMPTY: char = '.';
     pub(crate) const START: char = 'S';
     pub(crate) const END: char = 'E';
 
     #[derive(Debug, Clone, PartialEq)]
-    pub struct Cell<'a> {
+    pub struct Cell {
         pub value: char,
-        pub position: Span<'a>,
+        pub position: (usize, usize),
     }
 
-    pub type ParsedGrid<'a> = Vec<Vec<Cell<'a>>>;
+    pub type ParsedGrid = Vec<Vec<Cell>>;
 
     pub fn parse_input(input: &str) -> miette::Result<ParsedGrid> {
-        let span = Span::new(input);
-        let (_, grid) = parse(span).map_err(|e| miette::miette!("Failed to parse input: {}", e))?;
-        Ok(grid)
-    }
-
-    pub(crate) fn parse(input: Span) -> IResult<Span, ParsedGrid> {
-        separated_list1(
-            newline,
-            many1(
-                satisfy(|c| matches!(c, WALL | EMPTY | START | END)).map(|c| Cell {
-                    value: c,
-                    position: input,
-                }),
-            ),
-        )
-        .parse(input)
+        let grid = grid::parse_char_grid(input, &[WALL, EMPTY, START, END])?;
+        Ok(grid
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|(value, position)| Cell { value, position })
+                    .collect()
+            })
+            .collect())
     }
 }
 
@@ -87,19 +81,32 @@ mod graph {
     pub fn find_endpoints(
         parsed_grid: &parser::ParsedGrid,
     ) -> miette::Result<(Position, Position)> {
-        let start = find_cells(parsed_grid, |cell| cell.value == 'S')
-            .into_iter()
-            .next()
-            .ok_or(miette::miette!("Start position not found"))?;
-
-        let end = find_cells(parsed_grid, |cell| cell.value == 'E')
-            .into_iter()
-            .next()
-            .ok_or(miette::miette!("End position not found"))?;
+        let start = find_unique_cell(parsed_grid, parser::START, "start")?;
+        let end = find_unique_cell(parsed_grid, parser::END, "end")?;
 
         Ok((start, end))
     }
 
+    /// The one cell holding `marker`. A missing or repeated marker is an error, as
+    /// picking one of several would quietly change the race.
+    fn find_unique_cell(
+        parsed_grid: &parser::ParsedGrid,
+        marker: char,
+        name: &str,
+    ) -> miette::Result<Position> {
+        match find_cells(parsed_grid, |cell| cell.value == marker)[..] {
+            [position] => Ok(position),
+            [] => Err(miette::miette!("No {} position ({}) found", name, marker)),
+            ref positions => Err(miette::miette!(
+                "Expected one {} position ({}) but found {} at {:?}",
+                name,
+                marker,
+                positions.len(),
+                positions
+            )),
+        }
+    }
+
     pub fn create_pathfinding_grid(grid: &PathGrid) -> PathGrid {
         let mut pathfinding_grid = grid.clone();
         pathfinding_grid.invert();
@@ -150,7 +157,7 @@ mod pathing {
 // Shortcuts module - Handles finding and evaluating shortcuts
 mod shortcuts {
     use super::*;
-    use rayon::prelude::*;
+    use grid::{Direction, Pos};
     use std::collections::{HashMap, HashSet};
 
     pub fn find_candidates(grid: &PathGrid) -> miette::Result<HashSet<Position>> {
@@ -181,12 +188,13 @@ mod shortcuts {
         start: Position,
         end: Position,
         original_length: usize,
+        threshold: usize,
     ) -> miette::Result<HashMap<Position, usize>> {
         candidates
             .par_iter()
             .map(|&pos| -> miette::Result<Option<(Position, usize)>> {
                 let improvement = evaluate_shortcut(grid, pos, start, end, original_length)?;
-                Ok(if improvement >= SHORTCUT_THRESHOLD {
+                Ok(if improvement >= threshold {
                     Some((pos, improvement))
                 } else {
                     None
@@ -202,16 +210,44 @@ mod shortcuts {
         start: Position,
         end: Position,
         original_length: usize,
+    ) -> miette::Result<usize> {
+        evaluate_tunnel(
+            grid,
+            shortcut,
+            Direction::Right,
+            1,
+            start,
+            end,
+            original_length,
+        )
+    }
+
+    /// Steps saved by digging a straight tunnel of `k` cells from `from` towards `dir`,
+    /// less `k - 1` for the cells beyond the first, so a one cell tunnel is the plain
+    /// single-vertex cheat. The tunnel has to stay on the grid.
+    pub(crate) fn evaluate_tunnel(
+        grid: &PathGrid,
+        from: Position,
+        dir: Direction,
+        k: usize,
+        start: Position,
+        end: Position,
+        original_length: usize,
     ) -> miette::Result<usize> {
         let mut test_grid = grid.clone();
-        test_grid.add_vertex(shortcut);
+        let mut cell = Some(Pos::from(from));
+        for _ in 0..k {
+            let pos = cell
+                .filter(|pos| pos.in_bounds(grid.width, grid.height))
+                .ok_or_else(|| {
+                    miette::miette!("Tunnel from {:?} runs off the grid going {:?}", from, dir)
+                })?;
+            test_grid.add_vertex((pos.x, pos.y));
+            cell = pos.step(dir);
+        }
 
         let new_length = pathing::find_shortest_path(&test_grid, start, end)?;
-        if new_length < original_length {
-            Ok(original_length - new_length)
-        } else {
-            Ok(0)
-        }
+        Ok(original_length.saturating_sub(new_length + k.saturating_sub(1)))
     }
 
     // Core path finding functions
@@ -258,6 +294,7 @@ mod shortcuts {
         point: Position,
         start: Position,
         end: Position,
+        threshold: usize,
     ) -> miette::Result<HashSet<Position>> {
         let mut shortcuts = HashSet::new();
         let mut visited = HashSet::new();
@@ -283,7 +320,7 @@ mod shortcuts {
 
                     if let Ok(new_length) = pathing::find_shortest_path(&test_grid, start, end) {
                         let improvement = original_length - new_length;
-                        if improvement >= SHORTCUT_THRESHOLD {
+                        if improvement >= threshold {
                             shortcuts.insert(pos);
                         }
                     }
@@ -369,8 +406,11 @@ mod shortcuts {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use grid::Direction;
     use std::{collections::HashSet, time::Instant};
 
+    const EXAMPLE_THRESHOLD: usize = 10;
+
     const EXAMPLE_LARGE: &str = "\
 ###############
 #...#...#.....#
@@ -455,8 +495,14 @@ mod tests {
             start_time.elapsed()
         );
 
-        let improvements =
-            shortcuts::evaluate_candidates(&path_grid, &candidates, start, end, original_length)?;
+        let improvements = shortcuts::evaluate_candidates(
+            &path_grid,
+            &candidates,
+            start,
+            end,
+            original_length,
+            EXAMPLE_THRESHOLD,
+        )?;
         println!(
             "Evaluated {} improvements in {:?}",
             improvements.len(),
@@ -508,6 +554,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_tunnel() -> miette::Result<()> {
+        let parsed_grid = parser::parse_input(EXAMPLE_SMALL)?;
+        let grid = graph::create_grid(&parsed_grid)?;
+        let path_grid = graph::create_pathfinding_grid(&grid);
+        let (start, end) = graph::find_endpoints(&parsed_grid)?;
+        let original_length = pathing::find_shortest_path(&path_grid, start, end)?;
+        let tunnel = |from, dir, k| {
+            shortcuts::evaluate_tunnel(&path_grid, from, dir, k, start, end, original_length)
+        };
+
+        // A one cell tunnel is the single vertex cheat
+        let one_cell =
+            shortcuts::evaluate_shortcut(&path_grid, (4, 3), start, end, original_length)?;
+        assert_eq!(4, one_cell);
+        assert_eq!(one_cell, tunnel((4, 3), Direction::Up, 1)?);
+
+        // Digging down through (4, 2) as well reaches the same corridor but pays for it
+        let two_cells = tunnel((4, 2), Direction::Down, 2)?;
+        assert_eq!(3, two_cells);
+        assert!(two_cells < one_cell);
+
+        assert!(tunnel((4, 3), Direction::Down, 3).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_manhattan_radius() -> miette::Result<()> {
         let parsed_grid = parser::parse_input(EXAMPLE_SMALL)?;
@@ -545,7 +617,7 @@ mod tests {
         let start_time = Instant::now();
         println!("\nStarting large example test");
 
-        let result = process(EXAMPLE_LARGE)?;
+        let result = process_with_threshold(EXAMPLE_LARGE, EXAMPLE_THRESHOLD)?;
 
         // Expected results from the problem description
         let expected_counts = [
@@ -594,8 +666,14 @@ mod tests {
         println!("Found {} candidates", candidates.len());
 
         // Debug each candidate
-        let improvements =
-            shortcuts::evaluate_candidates(&path_grid, &candidates, start, end, original_length)?;
+        let improvements = shortcuts::evaluate_candidates(
+            &path_grid,
+            &candidates,
+            start,
+            end,
+            original_length,
+            EXAMPLE_THRESHOLD,
+        )?;
 
         println!("\nSignificant improvements:");
         for (pos, improvement) in improvements.iter() {
@@ -647,6 +725,7 @@ mod tests {
             start_pos,
             end_pos,
             original_length,
+            EXAMPLE_THRESHOLD,
         )?;
 
         println!("\nFound {} improvements:", improvements.len());
@@ -0,0 +1,25 @@
+//! Times the memoized part 2 solver on each example code at 25 robot levels. Building
+//! the presses as strings at that depth would never finish, so a slow run here means
+//! that path has crept back in.
+use day_21::part2::{solve_length, ROBOT_LEVELS};
+
+fn main() {
+    divan::main();
+}
+
+/// The example codes with the length of presses each needs at 25 levels
+const CODES: [(&str, u64); 5] = [
+    ("029A", 82050061710),
+    ("980A", 72242026390),
+    ("179A", 81251039228),
+    ("456A", 80786362258),
+    ("379A", 77985628636),
+];
+
+#[divan::bench(args = CODES.map(|(code, _)| code))]
+fn solve(code: &str) {
+    let length = solve_length(divan::black_box(code), ROBOT_LEVELS).unwrap();
+
+    let (_, expected) = CODES.iter().find(|&&(known, _)| known == code).unwrap();
+    assert_eq!(*expected, length, "{code} changed between runs");
+}
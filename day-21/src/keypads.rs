@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
 use std::hash::Hash;
@@ -31,6 +32,35 @@ pub struct Keypad<K: Key> {
 
 pub type Path = Vec<NodeIndex>;
 
+/// Orders two encoded paths between the same pair of keys, `Less` being the one the
+/// next robot up can type more cheaply:
+///
+/// 1. Shorter paths first.
+/// 2. Fewer turns, so identical presses are grouped (`<<^` over `<^<`). Repeating a
+///    press costs the robot above a single extra `A`.
+/// 3. By first direction, `<` before `v`/`^` before `>`. `<` is furthest from `A`
+///    on the directional keypad, so it is cheapest to visit while the robot above is
+///    still on its way out, and ending on `>` or `^` leaves it next to `A`.
+///
+/// Any remaining tie is broken by the path itself, so the choice is deterministic.
+pub fn compare_encoded_paths(a: &str, b: &str) -> Ordering {
+    fn turns(path: &str) -> usize {
+        path.as_bytes().windows(2).filter(|w| w[0] != w[1]).count()
+    }
+
+    fn first_direction_rank(path: &str) -> usize {
+        match path.chars().next() {
+            Some('<') => 0,
+            Some('v') | Some('^') => 1,
+            Some('>') => 2,
+            _ => 3,
+        }
+    }
+
+    let key = |path: &str| (path.len(), turns(path), first_direction_rank(path));
+    key(a).cmp(&key(b)).then_with(|| a.cmp(b))
+}
+
 impl<K: Key> Keypad<K> {
     pub fn new(keys: Vec<Vec<K>>, exclude: impl Fn(&K) -> bool) -> Self {
         let mut keypad = Self {
@@ -145,41 +175,54 @@ impl<K: Key> Keypad<K> {
         }
 
         let mut result = String::new();
-        let mut current_key =
-            current.unwrap_or_else(|| K::from_char('A').expect("Invalid start character: A"));
-        let mut chars = sequence.chars();
-
-        while let Some(c) = chars.next() {
-            let target =
-                K::from_char(c).ok_or_else(|| miette::miette!("Invalid character: {}", c))?;
+        for step in self.moves(sequence, current) {
+            result.push_str(&step?);
+            result.push('A');
+        }
 
-            let path_options = self.find_paths(current_key, target)?;
+        Ok(result)
+    }
 
-            let mut scored_paths: Vec<(String, usize)> = path_options
-                .into_iter()
-                .filter_map(|path| {
-                    self.encode_path_direction(path)
-                        .ok()
-                        .map(|encoded| (encoded.clone(), self.score_encoded_path(&encoded)))
-                })
-                .collect();
+    /// Length of what [`encode_sequence`](Self::encode_sequence) would return, picking
+    /// the same moves but only ever holding one of them at a time
+    pub fn encode_sequence_len(&self, sequence: &str, current: Option<K>) -> Result<u64> {
+        if sequence.is_empty() {
+            return Ok(1);
+        }
 
-            scored_paths.sort_by_key(|(path, score)| (*score, path.len()));
+        self.moves(sequence, current)
+            .map(|step| Ok(step?.len() as u64 + 1))
+            .sum()
+    }
 
-            if let Some((best_path, _)) = scored_paths.last() {
-                result.push_str(best_path);
-            }
+    /// The presses that move from one key of `sequence` to the next, before each `A`
+    fn moves<'a>(
+        &'a self,
+        sequence: &'a str,
+        current: Option<K>,
+    ) -> impl Iterator<Item = Result<String>> + 'a {
+        let mut current_key =
+            current.unwrap_or_else(|| K::from_char('A').expect("Invalid start character: A"));
 
-            result.push('A');
+        sequence.chars().map(move |c| {
+            let target =
+                K::from_char(c).ok_or_else(|| miette::miette!("Invalid character: {}", c))?;
+            let best_path = self.best_path(current_key, target)?;
             current_key = target;
-        }
-
-        Ok(result)
+            Ok(best_path)
+        })
     }
 
-    fn score_encoded_path(&self, path: &str) -> usize {
-        let patterns = ["^^", "vv", "<<", ">>", "AA"];
-        patterns.iter().map(|p| path.matches(p).count()).sum()
+    /// The encoded path between two keys that the encoder prefers, empty when there
+    /// is nothing to press
+    fn best_path(&self, from: K, to: K) -> Result<String> {
+        let path_options = self.find_paths(from, to)?;
+
+        Ok(path_options
+            .into_iter()
+            .filter_map(|path| self.encode_path_direction(path).ok())
+            .min_by(|a, b| compare_encoded_paths(a, b))
+            .unwrap_or_default())
     }
 
     pub fn find_paths(&self, start: K, end: K) -> Result<Vec<Path>> {
@@ -6,12 +6,17 @@ use std::collections::HashMap;
 pub const ROBOT_LEVELS: usize = 1;
 
 pub fn process(input: &str) -> miette::Result<(HashMap<String, String>, usize)> {
-    let input_sequences: Vec<String> = input.lines().map(|s| s.to_string()).collect();
+    let input: &str = &grid::normalize(input);
+    // Check every code up front so a malformed one is reported before any encoding
+    let codes = input
+        .lines()
+        .map(|code| Ok((code.to_string(), numeric_part(code)?)))
+        .collect::<miette::Result<Vec<_>>>()?;
 
     // Process sequences in parallel
-    let solutions: HashMap<_, _> = input_sequences
+    let solutions: HashMap<_, _> = codes
         .par_iter() // Parallel iterator
-        .map(|sequence| {
+        .map(|(sequence, _)| {
             let numeric_keypad = create_numeric_keypad();
             let directional_keypad = create_directional_keypad();
 
@@ -38,26 +43,39 @@ pub fn process(input: &str) -> miette::Result<(HashMap<String, String>, usize)>
         .collect::<miette::Result<HashMap<_, _>>>()?;
 
     // Calculate complexity in parallel
-    let complexity = solutions
+    let complexity = codes
         .par_iter()
-        .map(|(k, v)| {
-            let key_nums = k
-                .chars()
-                .filter(|c| c.is_ascii_digit())
-                .collect::<String>()
-                .trim_start_matches('0')
-                .parse::<usize>()
-                .unwrap_or(0);
-            key_nums * v.len()
-        })
+        .map(|(code, number)| number * solutions[code].len())
         .sum();
 
     Ok((solutions, complexity))
 }
 
+/// The number a door code stands for, e.g. 29 for `029A`. Codes must be digits
+/// followed by a single `A`.
+pub(crate) fn numeric_part(code: &str) -> miette::Result<usize> {
+    let digits = code
+        .strip_suffix('A')
+        .filter(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| {
+            miette::miette!(
+                "Invalid code `{}`: expected digits followed by A, like 029A",
+                code
+            )
+        })?;
+
+    digits
+        .parse()
+        .map_err(|e| miette::miette!("Invalid code `{}`: {}", code, e))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{keypads::Key, numeric::NumericKey};
+    use crate::directional::DirectionalKey;
+    use crate::keypads::{compare_encoded_paths, Key, Keypad};
+    use crate::numeric::NumericKey;
+    use itertools::Itertools;
+    use std::cmp::Ordering;
 
     use super::*;
 
@@ -416,4 +434,147 @@ mod tests {
 
         Ok(())
     }
+
+    /// Fewest presses the human needs for `sequence` on `keypad` behind `robots`
+    /// directional keypads, trying every shortest path at every level
+    fn cheapest<K: Key>(
+        keypad: &Keypad<K>,
+        sequence: &str,
+        current: K,
+        robots: usize,
+    ) -> miette::Result<usize> {
+        let directional_keypad = create_directional_keypad();
+        let mut from = current;
+        let mut total = 0;
+
+        for c in sequence.chars() {
+            let to = K::from_char(c).unwrap();
+            let mut best = usize::MAX;
+            for path in keypad.find_paths(from, to)? {
+                let presses = keypad.encode_path_direction(path)? + "A";
+                let cost = match robots {
+                    0 => presses.len(),
+                    _ => cheapest(
+                        &directional_keypad,
+                        &presses,
+                        DirectionalKey::from_char('A').unwrap(),
+                        robots - 1,
+                    )?,
+                };
+                best = best.min(cost);
+            }
+            total += best;
+            from = to;
+        }
+
+        Ok(total)
+    }
+
+    #[test]
+    fn test_compare_encoded_paths_is_optimal() -> miette::Result<()> {
+        let numeric_keypad = create_numeric_keypad();
+        let directional_keypad = create_directional_keypad();
+        let keys = "0123456789A";
+
+        // Every single move on the door keypad, behind the two robots of the example
+        for (from, to) in keys.chars().cartesian_product(keys.chars()) {
+            let from = NumericKey::from_char(from).unwrap();
+            let level1 = numeric_keypad.encode_sequence(&to.to_string(), Some(from))?;
+            let level2 = directional_keypad.encode_sequence(&level1, None)?;
+            let chosen = directional_keypad.encode_sequence_len(&level2, None)?;
+
+            let optimal = cheapest(&numeric_keypad, &to.to_string(), from, 2)?;
+            assert_eq!(optimal as u64, chosen, "{from} -> {to}: {level1}");
+        }
+
+        for code in ["029A", "980A", "179A", "456A", "379A"] {
+            let optimal = cheapest(
+                &numeric_keypad,
+                code,
+                NumericKey::from_char('A').unwrap(),
+                2,
+            )?;
+            assert_eq!(optimal, process(code)?.0[code].len(), "{code}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_chosen_encoding_a_to_2_to_9() -> miette::Result<()> {
+        let numeric_keypad = create_numeric_keypad();
+        let directional_keypad = create_directional_keypad();
+
+        // Left before up out of A, then both ups before the final right
+        let level1 = numeric_keypad.encode_sequence("29", None)?;
+        assert_eq!("<^A^^>A", level1);
+
+        let level2 = directional_keypad.encode_sequence(&level1, None)?;
+        assert_eq!("v<<A>^A>A<AAv>A^A", level2);
+
+        assert_eq!(
+            Ordering::Less,
+            compare_encoded_paths("^^>", ">^^"),
+            "up before right"
+        );
+        assert_eq!(
+            Ordering::Less,
+            compare_encoded_paths("^^>", "^>^"),
+            "grouped presses"
+        );
+        assert_eq!(
+            Ordering::Less,
+            compare_encoded_paths("<^", "^<"),
+            "left first"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_invalid_code() {
+        let error = process("ZZZ").unwrap_err();
+        assert_eq!(
+            "Invalid code `ZZZ`: expected digits followed by A, like 029A",
+            error.to_string()
+        );
+
+        // No numeric part, or something other than digits before the A
+        assert!(process("A").is_err());
+        assert!(process("029A\n0B9A").is_err());
+    }
+
+    #[test]
+    fn test_numeric_part() -> miette::Result<()> {
+        assert_eq!(29, numeric_part("029A")?);
+        assert_eq!(0, numeric_part("000A")?);
+        assert!(numeric_part("029").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_sequence_len() -> miette::Result<()> {
+        let numeric_keypad = create_numeric_keypad();
+        let directional_keypad = create_directional_keypad();
+
+        for code in ["029A", "980A", "179A", "456A", "379A", ""] {
+            let level1 = numeric_keypad.encode_sequence(code, None)?;
+            assert_eq!(
+                level1.len() as u64,
+                numeric_keypad.encode_sequence_len(code, None)?
+            );
+
+            let level2 = directional_keypad.encode_sequence(&level1, None)?;
+            assert_eq!(
+                level2.len() as u64,
+                directional_keypad.encode_sequence_len(&level1, None)?
+            );
+
+            let level3 = directional_keypad.encode_sequence(&level2, None)?;
+            assert_eq!(
+                level3.len() as u64,
+                directional_keypad.encode_sequence_len(&level2, None)?
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -1,17 +1,104 @@
+use std::collections::HashMap;
+
+use crate::directional::{create_directional_keypad, DirectionalKey};
+use crate::keypads::{Key, Keypad};
+use crate::numeric::create_numeric_keypad;
+use crate::part1::numeric_part;
+
+/// Directional keypads between the door and the human in part 2
+pub const ROBOT_LEVELS: usize = 25;
+
 #[tracing::instrument]
-pub fn process(_input: &str) -> miette::Result<String> {
-    todo!("day 01 - part 2");
+pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
+    let complexity = input
+        .lines()
+        .map(|code| Ok(numeric_part(code)? as u64 * solve_length(code, ROBOT_LEVELS)?))
+        .sum::<miette::Result<u64>>()?;
+    Ok(complexity.to_string())
+}
+
+/// Length of the presses a human makes to type `code` on the door through `robots`
+/// directional keypads. Only lengths are passed between levels, memoized per key pair
+/// and level, so 25 levels stay cheap where the presses themselves would run to
+/// trillions of characters.
+pub fn solve_length(code: &str, robots: usize) -> miette::Result<u64> {
+    let numeric_keypad = create_numeric_keypad();
+    let directional_keypad = create_directional_keypad();
+
+    let presses = numeric_keypad.encode_sequence(code, None)?;
+    directional_length(&directional_keypad, &presses, robots, &mut HashMap::new())
+}
+
+/// Length of `presses` once encoded through `robots` more directional keypads, each
+/// starting on `A`. The last keypad is measured with
+/// [`encode_sequence_len`](Keypad::encode_sequence_len) rather than encoded.
+fn directional_length(
+    keypad: &Keypad<DirectionalKey>,
+    presses: &str,
+    robots: usize,
+    memo: &mut HashMap<(char, char, usize), u64>,
+) -> miette::Result<u64> {
+    match robots {
+        0 => return Ok(presses.len() as u64),
+        1 => return keypad.encode_sequence_len(presses, None),
+        _ => {}
+    }
+
+    let mut from = 'A';
+    let mut total = 0;
+    for to in presses.chars() {
+        let length = match memo.get(&(from, to, robots)) {
+            Some(&length) => length,
+            None => {
+                let start = DirectionalKey::from_char(from)
+                    .ok_or_else(|| miette::miette!("Invalid character: {}", from))?;
+                let moves = keypad.encode_sequence(&to.to_string(), Some(start))?;
+                let length = directional_length(keypad, &moves, robots - 1, memo)?;
+                memo.insert((from, to, robots), length);
+                length
+            }
+        };
+        total += length;
+        from = to;
+    }
+
+    Ok(total)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const CODES: [(&str, u64); 5] = [
+        ("029A", 29),
+        ("980A", 980),
+        ("179A", 179),
+        ("456A", 456),
+        ("379A", 379),
+    ];
+
     #[test]
     fn test_process() -> miette::Result<()> {
-        todo!("haven't built test yet");
-        let input = "";
-        assert_eq!("", process(input)?);
+        let input = CODES.map(|(code, _)| code).join("\n");
+        assert_eq!("154115708116294", process(&input)?);
+        assert!(process("12").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_length() -> miette::Result<()> {
+        // Two robots is part 1, which builds the presses in full
+        let (solutions, _) = crate::part1::process(&CODES.map(|(code, _)| code).join("\n"))?;
+        for (code, _) in CODES {
+            assert_eq!(solutions[code].len() as u64, solve_length(code, 2)?);
+        }
+
+        let complexity = CODES
+            .iter()
+            .map(|&(code, number)| Ok(number * solve_length(code, ROBOT_LEVELS)?))
+            .sum::<miette::Result<u64>>()?;
+        assert_eq!(154115708116294, complexity);
         Ok(())
     }
 }
@@ -11,6 +11,7 @@ pub const VALUE_COUNT: usize = 2000;
 
 #[tracing::instrument]
 pub fn process(input: &str) -> miette::Result<String> {
+    let input: &str = &grid::normalize(input);
     let mut input = input
         .lines()
         .map(|line| line.parse::<usize>().map_err(ParseError))
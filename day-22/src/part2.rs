@@ -74,6 +74,7 @@ impl SecretNumber {
 
 #[tracing::instrument(skip_all)]
 pub fn process(input: &str) -> Result<String, PuzzleError> {
+    let input: &str = &grid::normalize(input);
     let mut buyers = input
         .lines()
         .map(|line| line.parse().map_err(PuzzleError::Parse))
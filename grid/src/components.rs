@@ -0,0 +1,92 @@
+use std::{collections::HashSet, hash::Hash};
+
+/// Flood fills from each of `nodes` in turn, grouping every node reachable through
+/// `neighbors` where `same(node, neighbor)` holds for each step taken.
+///
+/// Nodes already claimed by an earlier component are skipped, so each node appears
+/// in exactly one component. Components come out in the order their first node
+/// appears in `nodes`.
+pub fn connected_components<N, I, F, S>(
+    nodes: impl IntoIterator<Item = N>,
+    neighbors: F,
+    same: S,
+) -> Vec<Vec<N>>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+    F: Fn(N) -> I,
+    S: Fn(N, N) -> bool,
+{
+    let mut visited = HashSet::new();
+    let mut components = Vec::new();
+
+    for start in nodes {
+        if !visited.insert(start) {
+            continue;
+        }
+
+        let mut component = vec![start];
+        let mut queue = vec![start];
+        while let Some(current) = queue.pop() {
+            for next in neighbors(current) {
+                if same(current, next) && visited.insert(next) {
+                    component.push(next);
+                    queue.push(next);
+                }
+            }
+        }
+        components.push(component);
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pos;
+
+    #[test]
+    fn test_two_components() {
+        let grid = ["AAB", "ABB", "BBB"].map(|row| row.chars().collect::<Vec<_>>());
+        let at = |pos: Pos| grid[pos.y][pos.x];
+        let nodes = (0..3).flat_map(|y| (0..3).map(move |x| Pos::new(x, y)));
+
+        let mut components = connected_components(
+            nodes,
+            |pos| pos.neighbors4().filter(|next| next.in_bounds(3, 3)),
+            |a, b| at(a) == at(b),
+        );
+        components.iter_mut().for_each(|component| component.sort());
+
+        assert_eq!(
+            vec![
+                vec![Pos::new(0, 0), Pos::new(0, 1), Pos::new(1, 0)],
+                vec![
+                    Pos::new(0, 2),
+                    Pos::new(1, 1),
+                    Pos::new(1, 2),
+                    Pos::new(2, 0),
+                    Pos::new(2, 1),
+                    Pos::new(2, 2),
+                ],
+            ],
+            components
+        );
+    }
+
+    #[test]
+    fn test_diagonal_is_not_connected() {
+        let grid = ["AB", "BA"].map(|row| row.chars().collect::<Vec<_>>());
+        let at = |pos: Pos| grid[pos.y][pos.x];
+        let nodes = (0..2).flat_map(|y| (0..2).map(move |x| Pos::new(x, y)));
+
+        let components = connected_components(
+            nodes,
+            |pos| pos.neighbors4().filter(|next| next.in_bounds(2, 2)),
+            |a, b| at(a) == at(b),
+        );
+        assert_eq!(4, components.len());
+        assert!(components.iter().all(|component| component.len() == 1));
+    }
+}
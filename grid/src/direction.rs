@@ -0,0 +1,98 @@
+/// One of the four grid directions, with `y` growing downwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All four directions, clockwise from `Up`
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// Position in [`Direction::ALL`], handy for per-direction lookup tables
+    pub const fn index(self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Right => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
+        }
+    }
+
+    /// `(dx, dy)` for one step in this direction
+    pub const fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    pub const fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub const fn turn_left(self) -> Self {
+        self.opposite().turn_right()
+    }
+
+    pub const fn opposite(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_matches_all() {
+        for (i, direction) in Direction::ALL.into_iter().enumerate() {
+            assert_eq!(i, direction.index());
+        }
+    }
+
+    #[test]
+    fn test_turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.opposite(), direction.turn_right().turn_right());
+            assert_eq!(direction, direction.turn_right().turn_left());
+            assert_eq!(direction, direction.opposite().opposite());
+            assert_eq!(
+                direction,
+                (0..4).fold(direction, |facing, _| facing.turn_right())
+            );
+        }
+        assert_eq!(Direction::Right, Direction::Up.turn_right());
+        assert_eq!(Direction::Left, Direction::Up.turn_left());
+    }
+
+    #[test]
+    fn test_delta() {
+        for direction in Direction::ALL {
+            let (dx, dy) = direction.delta();
+            assert_eq!(1, dx.abs() + dy.abs());
+            assert_eq!((-dx, -dy), direction.opposite().delta());
+        }
+        assert_eq!((0, -1), Direction::Up.delta());
+        assert_eq!((1, 0), Direction::Right.delta());
+    }
+}
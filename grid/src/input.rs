@@ -0,0 +1,57 @@
+use std::{borrow::Cow, path::Path};
+
+use miette::{Context, IntoDiagnostic};
+
+/// Converts `\r\n` line endings to `\n` and drops a single trailing newline, so
+/// parsers see the same text however the input was saved. Borrows when there is
+/// nothing to change.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    if input.contains("\r\n") {
+        let mut text = input.replace("\r\n", "\n");
+        if text.ends_with('\n') {
+            text.pop();
+        }
+        Cow::Owned(text)
+    } else {
+        Cow::Borrowed(input.strip_suffix('\n').unwrap_or(input))
+    }
+}
+
+/// Reads a puzzle input from disk, [normalized](normalize)
+pub fn load_input(path: impl AsRef<Path>) -> miette::Result<String> {
+    let path = path.as_ref();
+    let input = std::fs::read_to_string(path)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("reading {}", path.display()))?;
+
+    Ok(normalize(&input).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!("1,2\n3,4", normalize("1,2\n3,4"));
+        assert_eq!("1,2\n3,4", normalize("1,2\n3,4\n"));
+        assert_eq!("1,2\n3,4", normalize("1,2\r\n3,4\r\n"));
+        assert_eq!("", normalize("\n"));
+
+        // Only one trailing newline goes, blank lines the puzzle relies on stay
+        assert_eq!("a\n\nb\n", normalize("a\n\nb\n\n"));
+        assert!(matches!(normalize("a\nb\n"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_load_input() -> miette::Result<()> {
+        let path = std::env::temp_dir().join(format!("grid-load-input-{}.txt", std::process::id()));
+        std::fs::write(&path, "12\r\n34\r\n").into_diagnostic()?;
+        let loaded = load_input(&path);
+        std::fs::remove_file(&path).into_diagnostic()?;
+
+        assert_eq!("12\n34", loaded?);
+        assert!(load_input(&path).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+pub mod components;
+pub mod direction;
+pub mod input;
+pub mod matrix;
+pub mod parse;
+pub mod pos;
+pub mod search;
+
+pub use components::connected_components;
+pub use direction::Direction;
+pub use input::{load_input, normalize};
+pub use parse::parse_char_grid;
+pub use pos::Pos;
+pub use search::astar_grid;
@@ -0,0 +1,61 @@
+/// Rows become columns: `result[x][y] == matrix[y][x]`. Rows are assumed to be
+/// the same length as the first.
+pub fn transpose<T: Clone>(matrix: &[Vec<T>]) -> Vec<Vec<T>> {
+    let width = matrix.first().map_or(0, |row| row.len());
+    (0..width)
+        .map(|x| matrix.iter().map(|row| row[x].clone()).collect())
+        .collect()
+}
+
+/// Mirrors left to right by reversing each row
+pub fn reverse_rows<T: Clone>(matrix: &[Vec<T>]) -> Vec<Vec<T>> {
+    matrix
+        .iter()
+        .map(|row| row.iter().rev().cloned().collect())
+        .collect()
+}
+
+/// Turns a quarter turn clockwise, so the first column read bottom to top
+/// becomes the first row
+pub fn rotate90<T: Clone>(matrix: &[Vec<T>]) -> Vec<Vec<T>> {
+    reverse_rows(&transpose(matrix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Vec<char>> {
+        vec![vec!['a', 'b', 'c'], vec!['d', 'e', 'f']]
+    }
+
+    #[test]
+    fn test_transpose() {
+        assert_eq!(
+            vec![vec!['a', 'd'], vec!['b', 'e'], vec!['c', 'f']],
+            transpose(&sample())
+        );
+        assert_eq!(sample(), transpose(&transpose(&sample())));
+        assert!(transpose::<char>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_reverse_rows() {
+        assert_eq!(
+            vec![vec!['c', 'b', 'a'], vec!['f', 'e', 'd']],
+            reverse_rows(&sample())
+        );
+        assert_eq!(sample(), reverse_rows(&reverse_rows(&sample())));
+    }
+
+    #[test]
+    fn test_rotate90() {
+        assert_eq!(
+            vec![vec!['d', 'a'], vec!['e', 'b'], vec!['f', 'c']],
+            rotate90(&sample())
+        );
+
+        let full_turn = (0..4).fold(sample(), |matrix, _| rotate90(&matrix));
+        assert_eq!(sample(), full_turn);
+    }
+}
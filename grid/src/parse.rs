@@ -0,0 +1,106 @@
+use miette::{miette, LabeledSpan};
+use nom::{
+    character::complete::{line_ending, satisfy},
+    combinator::opt,
+    multi::{many1, separated_list1},
+    IResult,
+};
+use nom_locate::{position, LocatedSpan};
+
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+/// Each cell's character with its 0-based `(col, row)`
+pub type CharGrid = Vec<Vec<(char, (usize, usize))>>;
+
+/// Parses a rectangular-ish block of characters, one row per line. Lines may end in
+/// `\n` or `\r\n`, and a single trailing line ending is allowed. Any character not in
+/// `allowed` is reported with its line and column and labelled in the source.
+pub fn parse_char_grid(input: &str, allowed: &[char]) -> miette::Result<CharGrid> {
+    let (rest, grid) = match rows(allowed)(Span::new(input)) {
+        Ok(parsed) => parsed,
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => (e.input, vec![]),
+        Err(nom::Err::Incomplete(_)) => unreachable!("complete parsers never ask for more"),
+    };
+    let (rest, _) = opt(line_ending::<_, nom::error::Error<Span>>)(rest)
+        .map_err(|e| miette!("Failed to parse input: {}", e))?;
+
+    match rest.fragment().chars().next() {
+        None => Ok(grid),
+        Some(c) => {
+            let offset = rest.location_offset();
+            Err(miette!(
+                labels = vec![LabeledSpan::at(
+                    offset..offset + c.len_utf8(),
+                    "not a grid character"
+                )],
+                "Invalid character {:?} at line {}, column {}",
+                c,
+                rest.location_line(),
+                rest.get_utf8_column()
+            )
+            .with_source_code(input.to_string()))
+        }
+    }
+}
+
+fn rows<'a>(allowed: &'a [char]) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, CharGrid> + 'a {
+    separated_list1(line_ending, many1(cell(allowed)))
+}
+
+fn cell<'a>(
+    allowed: &'a [char],
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, (char, (usize, usize))> + 'a {
+    move |input| {
+        let (input, pos) = position(input)?;
+        let (input, c) = satisfy(|c| allowed.contains(&c))(input)?;
+        let coords = (pos.get_utf8_column() - 1, pos.location_line() as usize - 1);
+        Ok((input, (c, coords)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALLOWED: [char; 4] = ['#', '.', 'S', 'E'];
+
+    #[test]
+    fn test_parse_char_grid() -> miette::Result<()> {
+        let grid = parse_char_grid("#S.\n.E#", &ALLOWED)?;
+        assert_eq!(
+            vec![
+                vec![('#', (0, 0)), ('S', (1, 0)), ('.', (2, 0))],
+                vec![('.', (0, 1)), ('E', (1, 1)), ('#', (2, 1))],
+            ],
+            grid
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_endings() -> miette::Result<()> {
+        let expected = parse_char_grid("#S.\n.E#", &ALLOWED)?;
+        assert_eq!(expected, parse_char_grid("#S.\r\n.E#", &ALLOWED)?);
+        assert_eq!(expected, parse_char_grid("#S.\r\n.E#\r\n", &ALLOWED)?);
+        assert_eq!(expected, parse_char_grid("#S.\n.E#\n", &ALLOWED)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_character() {
+        let error = parse_char_grid("#S.\n.Ex\n", &ALLOWED).unwrap_err();
+        assert!(error.to_string().contains("line 2, column 3"));
+
+        let labels = error.labels().expect("error points at the character");
+        let spans = labels
+            .map(|label| (label.offset(), label.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(6, 1)], spans);
+
+        // Also when it is the very first character or starts a line
+        let error = parse_char_grid("x#", &ALLOWED).unwrap_err();
+        assert!(error.to_string().contains("line 1, column 1"));
+        let error = parse_char_grid("##\r\nx#", &ALLOWED).unwrap_err();
+        assert!(error.to_string().contains("line 2, column 1"));
+    }
+}
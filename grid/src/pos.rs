@@ -0,0 +1,88 @@
+use crate::Direction;
+
+/// A cell in a grid, `x` across and `y` down from the top left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pos {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Pos {
+    pub const fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// The neighbouring cell in `direction`, or `None` if that would go below zero
+    pub fn step(self, direction: Direction) -> Option<Pos> {
+        let (dx, dy) = direction.delta();
+        Some(Pos::new(
+            self.x.checked_add_signed(dx)?,
+            self.y.checked_add_signed(dy)?,
+        ))
+    }
+
+    /// The up to four cells sharing an edge with this one, in [`Direction::ALL`] order.
+    /// Only the lower bound is checked, see [`Pos::in_bounds`] for the upper one.
+    pub fn neighbors4(self) -> impl Iterator<Item = Pos> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(direction))
+    }
+
+    pub fn in_bounds(self, width: usize, height: usize) -> bool {
+        self.x < width && self.y < height
+    }
+
+    pub fn manhattan(self, other: Pos) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((x, y): (usize, usize)) -> Self {
+        Pos::new(x, y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step() {
+        let pos = Pos::new(2, 5);
+        assert_eq!(Some(Pos::new(2, 4)), pos.step(Direction::Up));
+        assert_eq!(Some(Pos::new(3, 5)), pos.step(Direction::Right));
+        assert_eq!(Some(Pos::new(2, 6)), pos.step(Direction::Down));
+        assert_eq!(Some(Pos::new(1, 5)), pos.step(Direction::Left));
+
+        assert_eq!(None, Pos::new(0, 0).step(Direction::Up));
+        assert_eq!(None, Pos::new(0, 0).step(Direction::Left));
+    }
+
+    #[test]
+    fn test_neighbors4() {
+        let neighbors = Pos::new(1, 1).neighbors4().collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Pos::new(1, 0),
+                Pos::new(2, 1),
+                Pos::new(1, 2),
+                Pos::new(0, 1)
+            ],
+            neighbors
+        );
+
+        // The corner only has two
+        let neighbors = Pos::new(0, 0).neighbors4().collect::<Vec<_>>();
+        assert_eq!(vec![Pos::new(1, 0), Pos::new(0, 1)], neighbors);
+    }
+
+    #[test]
+    fn test_in_bounds_and_manhattan() {
+        assert!(Pos::new(6, 6).in_bounds(7, 7));
+        assert!(!Pos::new(7, 0).in_bounds(7, 7));
+        assert_eq!(12, Pos::new(0, 0).manhattan(Pos::new(6, 6)));
+        assert_eq!(4, Pos::new(5, 1).manhattan(Pos::new(3, 3)));
+    }
+}
@@ -0,0 +1,116 @@
+use pathfinding::prelude::astar;
+
+use crate::Pos;
+
+/// Cheapest path from `start` to `end` moving up, down, left or right, never onto a
+/// wall or off the grid. `walls` is indexed `[y][x]`, and its size is the grid's.
+///
+/// `cost_fn(from, to)` prices each step and `heuristic(pos)` estimates the rest of the
+/// way to `end`; it must never overestimate for the result to be the cheapest. Returns
+/// the total cost and every position on the path, `start` and `end` included.
+pub fn astar_grid<C, H>(
+    walls: &[Vec<bool>],
+    start: Pos,
+    end: Pos,
+    cost_fn: C,
+    heuristic: H,
+) -> Option<(usize, Vec<Pos>)>
+where
+    C: Fn(Pos, Pos) -> usize,
+    H: Fn(Pos) -> usize,
+{
+    let is_open = |pos: Pos| {
+        walls
+            .get(pos.y)
+            .and_then(|row| row.get(pos.x))
+            .is_some_and(|&wall| !wall)
+    };
+    if !is_open(start) || !is_open(end) {
+        return None;
+    }
+
+    let (path, cost) = astar(
+        &start,
+        |&pos| {
+            pos.neighbors4()
+                .filter(|&next| is_open(next))
+                .map(|next| (next, cost_fn(pos, next)))
+                .collect::<Vec<_>>()
+        },
+        |&pos| heuristic(pos),
+        |&pos| pos == end,
+    )?;
+
+    Some((cost, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The first 12 bytes of the day-18 example on its 7x7 grid
+    fn day18_walls() -> Vec<Vec<bool>> {
+        let bytes = [
+            (5, 4),
+            (4, 2),
+            (4, 5),
+            (3, 0),
+            (2, 1),
+            (6, 3),
+            (2, 4),
+            (1, 5),
+            (0, 6),
+            (3, 3),
+            (2, 6),
+            (5, 1),
+        ];
+        let mut walls = vec![vec![false; 7]; 7];
+        for (x, y) in bytes {
+            walls[y][x] = true;
+        }
+        walls
+    }
+
+    #[test]
+    fn test_day18_example() {
+        let walls = day18_walls();
+        let (start, end) = (Pos::new(0, 0), Pos::new(6, 6));
+
+        let (cost, path) =
+            astar_grid(&walls, start, end, |_, _| 1, |pos| pos.manhattan(end)).unwrap();
+        assert_eq!(22, cost);
+        assert_eq!(23, path.len());
+        assert_eq!((Some(&start), Some(&end)), (path.first(), path.last()));
+        assert!(path.iter().all(|pos| !walls[pos.y][pos.x]));
+        assert!(path.windows(2).all(|step| step[0].manhattan(step[1]) == 1));
+    }
+
+    #[test]
+    fn test_weighted_steps() {
+        // Open 3x3 grid where stepping onto the middle column costs 10
+        let walls = vec![vec![false; 3]; 3];
+        let (start, end) = (Pos::new(0, 0), Pos::new(2, 0));
+        let cost_fn = |_: Pos, to: Pos| if to.x == 1 { 10 } else { 1 };
+
+        let (cost, _) = astar_grid(&walls, start, end, cost_fn, |_| 0).unwrap();
+        assert_eq!(11, cost);
+    }
+
+    #[test]
+    fn test_no_path() {
+        let mut walls = vec![vec![false; 3]; 3];
+        walls[1] = vec![true; 3];
+        let (start, end) = (Pos::new(0, 0), Pos::new(0, 2));
+        assert_eq!(None, astar_grid(&walls, start, end, |_, _| 1, |_| 0));
+
+        // Starting on a wall or off the grid
+        assert_eq!(
+            None,
+            astar_grid(&walls, Pos::new(0, 1), end, |_, _| 1, |_| 0)
+        );
+        assert_eq!(
+            None,
+            astar_grid(&walls, Pos::new(5, 5), end, |_, _| 1, |_| 0)
+        );
+    }
+}
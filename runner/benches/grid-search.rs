@@ -0,0 +1,85 @@
+//! Criterion benchmarks for the grid search days, run with
+//! `cargo bench -p runner --features bench`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day_18::params::Params;
+
+const DAY_16_EXAMPLE: &str = "\
+#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
+
+const DAY_18_EXAMPLE: &str = "\
+5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0";
+
+const DAY_20_EXAMPLE: &str = "\
+###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
+
+fn grid_search(c: &mut Criterion) {
+    c.bench_function("day-16 part 1", |b| {
+        b.iter(|| day_16::part1::process(black_box(DAY_16_EXAMPLE)).unwrap())
+    });
+    c.bench_function("day-18 part 1", |b| {
+        b.iter(|| {
+            day_18::part1::process_with_params(black_box(DAY_18_EXAMPLE), Params::EXAMPLE).unwrap()
+        })
+    });
+    c.bench_function("day-20 part 1", |b| {
+        b.iter(|| day_20::part1::process_with_threshold(black_box(DAY_20_EXAMPLE), 30).unwrap())
+    });
+}
+
+criterion_group!(benches, grid_search);
+criterion_main!(benches);
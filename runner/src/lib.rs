@@ -0,0 +1,5 @@
+pub mod run;
+pub mod solver;
+
+pub use run::run_all;
+pub use solver::{solver, solvers, Solver};
@@ -0,0 +1,50 @@
+use std::{path::PathBuf, time::Instant};
+
+use clap::Parser;
+use miette::{miette, Context};
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Parser, Debug)]
+#[clap(version, about = "Run one part of one day against an input file")]
+struct Args {
+    /// day to run, 1 to 25
+    #[clap(short, long)]
+    day: u8,
+    /// which part of the day, 1 or 2
+    #[clap(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: u8,
+    /// puzzle input to solve
+    #[clap(short, long)]
+    input: PathBuf,
+}
+
+fn main() -> miette::Result<()> {
+    // Timings go to stderr at info level unless RUST_LOG says otherwise,
+    // leaving stdout with just the answer
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .with_writer(std::io::stderr)
+        .init();
+
+    let args = Args::parse();
+    let solver =
+        runner::solver(args.day).ok_or_else(|| miette!("day {} is not registered", args.day))?;
+    let input = grid::load_input(&args.input)?;
+
+    let start = Instant::now();
+    let result = match args.part {
+        1 => solver.part1(&input),
+        _ => solver.part2(&input),
+    }
+    .wrap_err_with(|| format!("day {} part {}", args.day, args.part))?;
+    info!(
+        "day {} part {} took {:?}",
+        args.day,
+        args.part,
+        start.elapsed()
+    );
+
+    println!("{}", result);
+    Ok(())
+}
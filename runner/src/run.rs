@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+
+use rayon::prelude::*;
+
+use crate::solver::{solver, Solver};
+
+/// Runs every `(day, part)` in `inputs` that has a registered solver, in parallel,
+/// giving back each answer with how long it took, sorted by day then part.
+///
+/// A part that fails, or panics, reports its error in place of an answer so one bad
+/// input doesn't hide the rest. Entries for unregistered days or parts other than 1 and
+/// 2 are skipped.
+pub fn run_all(inputs: &HashMap<(u8, u8), String>) -> Vec<(u8, u8, String, Duration)> {
+    let mut results = inputs
+        .par_iter()
+        .filter(|(&(_, part), _)| part == 1 || part == 2)
+        .filter_map(|(&(day, part), input)| {
+            let solver = solver(day)?;
+
+            let start = Instant::now();
+            let answer = answer(solver, part, input);
+            Some((day, part, answer, start.elapsed()))
+        })
+        .collect::<Vec<_>>();
+
+    results.sort_by_key(|&(day, part, ..)| (day, part));
+    results
+}
+
+/// The answer to one part, or the error or panic message it stopped with
+fn answer(solver: &dyn Solver, part: u8, input: &str) -> String {
+    let result = catch_unwind(AssertUnwindSafe(|| match part {
+        1 => solver.part1(input),
+        _ => solver.part2(input),
+    }));
+
+    match result {
+        Ok(Ok(answer)) => answer,
+        Ok(Err(err)) => format!("error: {err}"),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned());
+            match message {
+                Some(message) => format!("error: panicked: {message}"),
+                None => "error: panicked".to_string(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_all() {
+        let day10 = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732";
+        let day17 = "\
+Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0";
+        let inputs = HashMap::from([
+            ((10, 1), day10.to_string()),
+            ((10, 2), day10.to_string()),
+            ((17, 1), day17.to_string()),
+            ((17, 2), day17.to_string()),
+            ((18, 2), "0,0".to_string()),
+            ((21, 2), "029A".to_string()),
+            ((25, 1), day10.to_string()),
+            ((10, 3), day10.to_string()),
+        ]);
+
+        let answers = run_all(&inputs)
+            .into_iter()
+            .map(|(day, part, answer, _)| (day, part, answer))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (10, 1, "36".to_string()),
+                (10, 2, "81".to_string()),
+                (17, 1, "5,7,3,0".to_string()),
+                (17, 2, "117440".to_string()),
+                (
+                    18,
+                    2,
+                    "error: No blocking coordinate found - reached end of input".to_string()
+                ),
+                (21, 2, (29 * 82050061710_u64).to_string()),
+            ],
+            answers
+        );
+    }
+
+    struct Panicking;
+
+    impl Solver for Panicking {
+        fn part1(&self, _input: &str) -> miette::Result<String> {
+            Err(miette::miette!("bad input"))
+        }
+
+        fn part2(&self, input: &str) -> miette::Result<String> {
+            let index: usize = input.parse().unwrap_or(usize::MAX);
+            Ok([1, 2, 3][index].to_string())
+        }
+    }
+
+    #[test]
+    fn test_answer_catches_panics() {
+        assert_eq!("error: bad input", answer(&Panicking, 1, ""));
+        assert_eq!("2", answer(&Panicking, 2, "1"));
+        assert!(answer(&Panicking, 2, "7").starts_with("error: panicked: index out of bounds"));
+    }
+}
@@ -0,0 +1,121 @@
+/// Both parts of one day's puzzle, each taking the raw puzzle input
+pub trait Solver: Sync {
+    fn part1(&self, input: &str) -> miette::Result<String>;
+    fn part2(&self, input: &str) -> miette::Result<String>;
+}
+
+/// Declares a unit struct per day implementing [`Solver`] with the given functions,
+/// and lists them all in [`solvers`].
+macro_rules! register {
+    ($($day:literal => $name:ident { part1: $part1:expr, part2: $part2:expr $(,)? })*) => {
+        $(
+            pub struct $name;
+
+            impl Solver for $name {
+                fn part1(&self, input: &str) -> miette::Result<String> {
+                    ($part1)(input)
+                }
+
+                fn part2(&self, input: &str) -> miette::Result<String> {
+                    ($part2)(input)
+                }
+            }
+        )*
+
+        /// Every registered day, in order
+        pub fn solvers() -> Vec<(u8, &'static dyn Solver)> {
+            vec![$(($day, &$name)),*]
+        }
+    };
+}
+
+// The same functions each day's own binaries run
+register! {
+    1 => Day01 { part1: day_01::part1::process, part2: day_01::part2::process }
+    2 => Day02 { part1: day_02::part1::process, part2: day_02::part2::process }
+    3 => Day03 { part1: day_03::part1::process, part2: day_03::part2::process }
+    4 => Day04 { part1: day_04::part1::process, part2: day_04::part2::process }
+    5 => Day05 { part1: day_05::part1::process, part2: day_05::part2::process }
+    6 => Day06 {
+        part1: day_06::part1::process,
+        part2: |input: &str| day_06::part2::process(input).map(|(_, answer)| answer),
+    }
+    7 => Day07 { part1: day_07::part1::process, part2: day_07::part2::process }
+    8 => Day08 { part1: day_08::part1::process, part2: day_08::part2::process }
+    9 => Day09 { part1: day_09::part1::process, part2: day_09::part2::process }
+    10 => Day10 { part1: day_10::part1::process, part2: day_10::part2::process }
+    11 => Day11 {
+        part1: |input: &str| day_11::part1_claude::process(input, 25),
+        part2: |input: &str| day_11::part2::process(input, 75),
+    }
+    12 => Day12 { part1: day_12::part1_claude::process, part2: day_12::part2::process }
+    13 => Day13 { part1: day_13::part1::process, part2: day_13::part2::process }
+    14 => Day14 { part1: day_14::part1::process, part2: day_14::part2::process }
+    15 => Day15 { part1: day_15::part1::process, part2: day_15::part2::process }
+    16 => Day16 { part1: day_16::part1::process, part2: day_16::part2::process }
+    17 => Day17 { part1: day_17::part1::process, part2: day_17::part2::process }
+    18 => Day18 { part1: day_18::part1::process, part2: day_18::part2::process }
+    19 => Day19 { part1: day_19::part1::process, part2: day_19::part2::process }
+    20 => Day20 { part1: day_20::part1::process, part2: day_20::part2::process }
+    21 => Day21 {
+        part1: |input: &str| day_21::part1::process(input).map(|(_, answer)| answer.to_string()),
+        part2: day_21::part2::process,
+    }
+    22 => Day22 {
+        part1: day_22::part1::process,
+        part2: |input: &str| Ok(day_22::part2::process(input)?),
+    }
+}
+
+/// The solver registered for `day`, if there is one
+pub fn solver(day: u8) -> Option<&'static dyn Solver> {
+    solvers()
+        .into_iter()
+        .find(|&(registered, _)| registered == day)
+        .map(|(_, solver)| solver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day17_through_trait() -> miette::Result<()> {
+        let solver = solver(17).expect("day 17 is registered");
+
+        let input = "\
+Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+        assert_eq!("4,6,3,5,6,3,5,2,1,0", solver.part1(input)?);
+
+        let input = "\
+Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0";
+        assert_eq!("117440", solver.part2(input)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2_registrations() -> miette::Result<()> {
+        assert_eq!("65601038650482", solver(11).unwrap().part2("125 17")?);
+        assert_eq!(
+            "154115708116294",
+            solver(21).unwrap().part2("029A\n980A\n179A\n456A\n379A")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_registered_days() {
+        let days = solvers().iter().map(|&(day, _)| day).collect::<Vec<_>>();
+        assert_eq!((1..=22).collect::<Vec<_>>(), days);
+        assert!(solver(0).is_none());
+        assert!(solver(23).is_none());
+    }
+}
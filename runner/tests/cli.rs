@@ -0,0 +1,81 @@
+use std::{path::PathBuf, process::Command};
+
+const DAY_18_EXAMPLE: &str = "\
+5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0";
+
+fn write_input(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("runner-{}-{}.txt", name, std::process::id()));
+    std::fs::write(&path, contents).expect("temp dir is writable");
+    path
+}
+
+fn run(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_runner"))
+        .args(args)
+        .output()
+        .expect("runner binary starts")
+}
+
+#[test]
+fn test_day18_example() {
+    let input = write_input("day18", DAY_18_EXAMPLE);
+    let input = input.to_str().unwrap();
+
+    // The binary solves on the real 71x71 grid, where the example's bytes
+    // only push the path around the top left corner
+    let output = run(&["--day", "18", "--part", "1", "--input", input]);
+    assert!(output.status.success());
+    assert_eq!("146\n", String::from_utf8_lossy(&output.stdout));
+
+    // ...and never cut it off
+    let output = run(&["--day", "18", "--part", "2", "--input", input]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No blocking coordinate found"));
+}
+
+#[test]
+fn test_bad_arguments() {
+    let input = write_input("bad-arguments", DAY_18_EXAMPLE);
+    let input = input.to_str().unwrap();
+
+    assert!(!run(&["--day", "18", "--part", "3", "--input", input])
+        .status
+        .success());
+    assert!(!run(&["--day", "30", "--part", "1", "--input", input])
+        .status
+        .success());
+    assert!(!run(&[
+        "--day",
+        "18",
+        "--part",
+        "1",
+        "--input",
+        "/no/such/input.txt"
+    ])
+    .status
+    .success());
+}